@@ -28,14 +28,52 @@ pub fn bucket_key(record: &UsageRecord, mode: &Command) -> String {
     match mode {
         Command::Daily => record.timestamp.format("%Y-%m-%d").to_string(),
         Command::Monthly => record.timestamp.format("%Y-%m").to_string(),
-        Command::Session => format!("{} | {}", record.project, record.session_id),
+        Command::Session { by_agent } => {
+            let key = format!("{} | {}", record.project, record.session_id);
+            with_agent(key, record, *by_agent)
+        }
+        Command::Project { by_agent } => with_agent(record.project.clone(), record, *by_agent),
+        Command::Branch => branch_label(record).to_string(),
         Command::Model => record.model.clone(),
+        Command::Tier => record
+            .service_tier
+            .clone()
+            .unwrap_or_else(|| "standard".to_string()),
         Command::ModelBurn { .. } => record.model.clone(),
+        Command::Errors { .. } => "errors".to_string(),
+        Command::Compactions => "compactions".to_string(),
+        Command::Tools => "tools".to_string(),
         Command::Watch { .. } => "watch".to_string(),
         Command::Bar { .. } => "bar".to_string(),
         Command::Plot { .. } => "plot".to_string(),
         Command::Subscription { .. } => "subscription".to_string(),
         Command::Account { .. } => "account".to_string(),
+        Command::ImportUsage { .. } => "import".to_string(),
+    }
+}
+
+/// The record's service tier, flagging priority requests that were priced
+/// at the standard rate so they aren't mistaken for real priority spend.
+fn tier_label(record: &UsageRecord, pricing: &dyn PricingMap) -> String {
+    if pricing.priority_at_standard_rate(record) {
+        return "priority (standard rate)".to_string();
+    }
+    record
+        .service_tier
+        .clone()
+        .unwrap_or_else(|| "standard".to_string())
+}
+
+/// Records from tools that don't log a branch are grouped together.
+fn branch_label(record: &UsageRecord) -> &str {
+    record.git_branch.as_deref().unwrap_or("(none)")
+}
+
+fn with_agent(key: String, record: &UsageRecord, by_agent: bool) -> String {
+    if by_agent {
+        format!("{key} | {}", record.agent_label())
+    } else {
+        key
     }
 }
 
@@ -46,6 +84,9 @@ struct BucketState {
     bucket: AggregatedBucket,
     projects: HashSet<String>,
     tools: HashSet<String>,
+    tiers: HashSet<String>,
+    agents: HashSet<String>,
+    branches: HashSet<String>,
     model_details: HashMap<String, ModelBucketDetail>,
 }
 
@@ -57,8 +98,12 @@ pub fn aggregate(
     let mut states: HashMap<String, BucketState> = HashMap::new();
 
     for r in records {
-        let key = bucket_key(r, mode);
+        let key = match mode {
+            Command::Tier => tier_label(r, pricing),
+            _ => bucket_key(r, mode),
+        };
         let record_cost = pricing.cost_for_record(r);
+        let discrepancy = pricing.cost_discrepancy(r);
 
         // Single entry lookup per record — no extra clones
         let state = states.entry(key).or_default();
@@ -70,11 +115,21 @@ pub fn aggregate(
             r.cache_read_input_tokens,
             record_cost,
         );
+        state.bucket.cache_creation_1h_input_tokens += r.cache_creation_1h_input_tokens;
+        state.bucket.reasoning_tokens += r.reasoning_tokens;
+        state.bucket.web_search_requests += r.web_search_requests;
+        state.bucket.web_fetch_requests += r.web_fetch_requests;
+        state.bucket.add_discrepancy(discrepancy);
 
         state.projects.insert(r.project.clone());
         // `tools` is a Vec<String> on AggregatedBucket for display; convert
         // from the typed enum at insertion time.
         state.tools.insert(r.provider.as_str().to_string());
+        state.agents.insert(r.agent_label().to_string());
+        if let Some(branch) = &r.git_branch {
+            state.branches.insert(branch.clone());
+        }
+        state.tiers.insert(tier_label(r, pricing));
 
         // Per-model detail
         let detail = state
@@ -86,7 +141,12 @@ pub fn aggregate(
                 output_tokens: 0,
                 cache_creation_input_tokens: 0,
                 cache_read_input_tokens: 0,
+                cache_creation_1h_input_tokens: 0,
+                reasoning_tokens: 0,
+                web_search_requests: 0,
+                web_fetch_requests: 0,
                 cost: None,
+                cost_discrepancy: None,
             });
         detail.accumulate(
             r.input_tokens,
@@ -95,6 +155,11 @@ pub fn aggregate(
             r.cache_read_input_tokens,
             record_cost,
         );
+        detail.cache_creation_1h_input_tokens += r.cache_creation_1h_input_tokens;
+        detail.reasoning_tokens += r.reasoning_tokens;
+        detail.web_search_requests += r.web_search_requests;
+        detail.web_fetch_requests += r.web_fetch_requests;
+        detail.add_discrepancy(discrepancy);
     }
 
     // Flatten BucketState into AggregatedBucket
@@ -114,6 +179,9 @@ pub fn aggregate(
             bucket.details = details;
             bucket.projects = state.projects.into_iter().collect();
             bucket.tools = state.tools.into_iter().collect();
+            bucket.tiers = state.tiers.into_iter().collect();
+            bucket.agents = state.agents.into_iter().collect();
+            bucket.branches = state.branches.into_iter().collect();
 
             (key, bucket)
         })
@@ -22,8 +22,12 @@ pub struct BurnReport {
     pub total: ModelBurnRow,
 }
 
-fn record_tokens(r: &UsageRecord) -> u64 {
-    r.input_tokens + r.output_tokens + r.cache_creation_input_tokens + r.cache_read_input_tokens
+pub(crate) fn record_tokens(r: &UsageRecord) -> u64 {
+    r.input_tokens
+        + r.output_tokens
+        + r.reasoning_tokens
+        + r.cache_creation_input_tokens
+        + r.cache_read_input_tokens
 }
 
 /// Sum of capped inter-record gaps within each group, summed across groups.
@@ -178,6 +182,11 @@ mod tests {
                     output_cost_per_token: 0.0,
                     cache_read_input_token_cost: None,
                     cache_creation_input_token_cost: None,
+                    cache_creation_input_token_cost_above_1hr: None,
+                    output_cost_per_reasoning_token: None,
+                    web_search_cost_per_request: None,
+                    web_fetch_cost_per_request: None,
+                    tiers: Vec::new(),
                 },
             );
         }
@@ -200,7 +209,19 @@ mod tests {
             output_tokens: 0,
             cache_creation_input_tokens: 0,
             cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
             account_uuid: None,
+            reported_cost: None,
         }
     }
 
@@ -1,7 +1,11 @@
+use std::path::PathBuf;
+
 use chrono::NaiveDate;
 use clap::{Parser, Subcommand, ValueEnum};
 
+use crate::cost::CostBasis;
 use crate::pricing::PricingSource;
+use crate::providers::import::ImportSource;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -13,9 +17,9 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Command>,
 
-    /// Start date filter (YYYY-MM-DD)
+    /// Start date filter (YYYY-MM-DD); for `import-usage`, the export format
     #[arg(long, global = true)]
-    pub from: Option<NaiveDate>,
+    pub from: Option<FromArg>,
 
     /// End date filter (YYYY-MM-DD)
     #[arg(long, global = true)]
@@ -33,7 +37,7 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub breakdown: bool,
 
-    /// Filter by project name (substring match)
+    /// Filter by project name (substring) or repository path (whole trailing components)
     #[arg(long, global = true)]
     pub project: Option<String>,
 
@@ -41,6 +45,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub tool: Option<String>,
 
+    /// Filter by git branch (exact match; Claude and Codex only)
+    #[arg(long, global = true)]
+    pub branch: Option<String>,
+
     /// Filter records to a specific account (requires prior `tku account add`).
     /// For now only Claude accounts are supported.
     #[arg(long, global = true)]
@@ -48,7 +56,8 @@ pub struct Cli {
 
     /// Columns to display (comma-separated).
     /// Use +col to add, -col to remove from defaults, or plain names to replace.
-    /// Available: period,input,output,cache_write,cache_read,cost,models,tools,projects
+    /// Available: period,input,output,reasoning,cache_write,cache_write_5m,cache_write_1h,cache_read,
+    /// cost,discrepancy,web_searches,models,tools,projects,tiers,agents,branches
     #[arg(long, global = true, value_delimiter = ',', allow_hyphen_values = true)]
     pub columns: Option<Vec<String>>,
 
@@ -56,6 +65,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub pricing_source: Option<PricingSource>,
 
+    /// Cost shown in reports: computed, reported, prefer-reported
+    #[arg(long, global = true)]
+    pub cost_basis: Option<CostBasis>,
+
     /// Currency code (ISO 4217) for cost display, e.g. EUR, GBP
     #[arg(long, global = true)]
     pub currency: Option<String>,
@@ -115,9 +128,23 @@ pub enum Command {
     /// Aggregate by month
     Monthly,
     /// Aggregate by session
-    Session,
+    Session {
+        /// Split each session into main-thread and per-subagent rows
+        #[arg(long)]
+        by_agent: bool,
+    },
+    /// Aggregate by project
+    Project {
+        /// Split each project into main-thread and per-subagent rows
+        #[arg(long)]
+        by_agent: bool,
+    },
+    /// Aggregate by git branch
+    Branch,
     /// Aggregate by model
     Model,
+    /// Aggregate by service tier (standard, priority, flex, batch)
+    Tier,
     /// Per-model burn rate: tokens/cost consumed per active minute/hour and sustained per calendar day
     ModelBurn {
         /// Cap idle gaps between consecutive messages at this many minutes when computing active time.
@@ -125,6 +152,16 @@ pub enum Command {
         #[arg(long, default_value = "5")]
         idle_gap: u64,
     },
+    /// API errors, rate limits and interrupted turns (Claude), with the tokens they wasted
+    Errors {
+        /// Group rows by: day, session, model
+        #[arg(long, default_value = "day")]
+        by: ErrorGrouping,
+    },
+    /// Context compactions per session (Claude), with the tokens summarized away
+    Compactions,
+    /// Tool calls per tool and MCP server (Claude, Codex), with the cost of the responses that made them
+    Tools,
     /// Live-updating cost monitor
     Watch {
         /// Show full table instead of compact summary line
@@ -143,7 +180,7 @@ pub enum Command {
         #[arg(long)]
         relative: bool,
     },
-    /// Show Claude Max/Pro subscription usage overview
+    /// Show Claude Max/Pro subscription usage overview (`--tool codex` for ChatGPT plans)
     #[command(visible_alias = "sub")]
     Subscription {
         /// Force live API fetch instead of using estimated usage
@@ -183,6 +220,12 @@ Typical workflow for two accounts:
         #[command(subcommand)]
         action: AccountAction,
     },
+    /// Import API usage from a billing export CSV (reported as tool "import").
+    /// Pick the export format with `--from anthropic-console|openai`
+    ImportUsage {
+        /// CSV file exported from the provider's usage page
+        file: PathBuf,
+    },
     /// Output JSON for status bars (waybar, i3bar, polybar)
     Bar {
         /// Timeframe to summarize
@@ -308,6 +351,64 @@ pub enum GraphPeriod {
     Month,
 }
 
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+pub enum ErrorGrouping {
+    Day,
+    Session,
+    Model,
+}
+
+impl ErrorGrouping {
+    /// Heading of the row-key column in `tku errors`.
+    pub fn column(&self) -> &'static str {
+        match self {
+            ErrorGrouping::Day => "Period",
+            ErrorGrouping::Session => "Session",
+            ErrorGrouping::Model => "Model",
+        }
+    }
+
+    /// Key the row key is written under in `tku errors --format json`.
+    pub fn json_key(&self) -> &'static str {
+        match self {
+            ErrorGrouping::Day => "period",
+            ErrorGrouping::Session => "session",
+            ErrorGrouping::Model => "model",
+        }
+    }
+}
+
+/// `--from` is a report's start date everywhere but `import-usage`, where
+/// it names the export format being imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FromArg {
+    Date(NaiveDate),
+    Source(ImportSource),
+}
+
+impl std::str::FromStr for FromArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = s.parse::<NaiveDate>() {
+            return Ok(Self::Date(date));
+        }
+        ImportSource::from_str(s, true)
+            .map(Self::Source)
+            .map_err(|_| {
+                let formats: Vec<String> = ImportSource::value_variants()
+                    .iter()
+                    .filter_map(|v| v.to_possible_value())
+                    .map(|v| v.get_name().to_string())
+                    .collect();
+                format!(
+                    "expected a date (YYYY-MM-DD) or an export format ({}), got {s:?}",
+                    formats.join(", ")
+                )
+            })
+    }
+}
+
 #[derive(ValueEnum, Debug, Clone, PartialEq)]
 pub enum OutputFormat {
     Table,
@@ -318,4 +419,69 @@ impl Cli {
     pub fn effective_command(&self) -> Command {
         self.command.clone().unwrap_or(Command::Daily)
     }
+
+    /// `--from` as a start date. An export format only makes sense for
+    /// `import-usage`.
+    pub fn start_date(&self) -> anyhow::Result<Option<NaiveDate>> {
+        match self.from {
+            None => Ok(None),
+            Some(FromArg::Date(date)) => Ok(Some(date)),
+            Some(FromArg::Source(_)) => anyhow::bail!(
+                "--from takes a start date (YYYY-MM-DD); export formats are only for `tku import-usage`"
+            ),
+        }
+    }
+
+    /// `--from` as the export format for `import-usage`.
+    pub fn import_source(&self) -> anyhow::Result<ImportSource> {
+        match self.from {
+            Some(FromArg::Source(source)) => Ok(source),
+            _ => {
+                anyhow::bail!("`tku import-usage` needs --from anthropic-console or --from openai")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("tku").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn from_takes_a_date_for_reports() {
+        let cli = parse(&["daily", "--from", "2026-05-01"]).unwrap();
+        assert_eq!(
+            cli.start_date().unwrap(),
+            NaiveDate::from_ymd_opt(2026, 5, 1)
+        );
+        assert!(cli.import_source().is_err());
+    }
+
+    #[test]
+    fn from_takes_an_export_format_for_import_usage() {
+        let cli = parse(&["import-usage", "--from", "openai", "usage.csv"]).unwrap();
+        assert_eq!(cli.import_source().unwrap(), ImportSource::Openai);
+
+        let report = parse(&["daily", "--from", "anthropic-console"]).unwrap();
+        assert!(report
+            .start_date()
+            .unwrap_err()
+            .to_string()
+            .contains("only for `tku import-usage`"));
+    }
+
+    #[test]
+    fn invalid_from_names_both_forms() {
+        let err = parse(&["daily", "--from", "2026-13-01"])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("a date (YYYY-MM-DD) or an export format (anthropic-console, openai)"),
+            "{err}"
+        );
+    }
 }
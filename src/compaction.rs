@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::aggregate::bucket_key;
+use crate::cli::Command;
+use crate::cost::PricingMap;
+use crate::types::UsageRecord;
+
+#[derive(Default)]
+pub struct CompactionRow {
+    pub session: String,
+    pub compactions: u64,
+    /// Of `compactions`, the ones run with `/compact`.
+    pub manual: u64,
+    /// Context size right before each compaction, summed.
+    pub summarized_tokens: u64,
+    /// Estimated cost of the summarization calls. None when unpriced.
+    pub summary_cost: Option<f64>,
+    /// Cost of the session's own usage, for scale. None when unpriced.
+    pub session_cost: Option<f64>,
+}
+
+impl CompactionRow {
+    /// Average context size a compaction kicked in at.
+    pub fn avg_context(&self) -> Option<u64> {
+        (self.compactions > 0).then(|| self.summarized_tokens / self.compactions)
+    }
+
+    fn add(&mut self, r: &UsageRecord, pricing: &dyn PricingMap) {
+        let cost = pricing.cost_for_record(r);
+        let sum = |acc: Option<f64>| match (acc, cost) {
+            (Some(acc), Some(c)) => Some(acc + c),
+            _ => None,
+        };
+        if r.is_compaction() {
+            self.compactions += 1;
+            if r.event.as_deref() == Some("manual_compact") {
+                self.manual += 1;
+            }
+            self.summarized_tokens += r.cache_read_input_tokens;
+            self.summary_cost = sum(self.summary_cost);
+        } else if r.event.is_none() {
+            self.session_cost = sum(self.session_cost);
+        }
+    }
+}
+
+pub struct CompactionReport {
+    pub rows: Vec<CompactionRow>,
+    pub total: CompactionRow,
+}
+
+/// Compactions per session (same keys as `tku session`), with the sessions'
+/// own cost alongside. Sessions that never compacted are left out.
+pub fn compute(records: &[UsageRecord], pricing: &dyn PricingMap) -> CompactionReport {
+    let new_row = |session: String| CompactionRow {
+        session,
+        summary_cost: Some(0.0),
+        session_cost: Some(0.0),
+        ..CompactionRow::default()
+    };
+    let mode = Command::Session { by_agent: false };
+
+    let mut sessions: HashMap<String, CompactionRow> = HashMap::new();
+    for r in records {
+        let key = bucket_key(r, &mode);
+        sessions
+            .entry(key.clone())
+            .or_insert_with(|| new_row(key))
+            .add(r, pricing);
+    }
+
+    let mut total = new_row("TOTAL".to_string());
+    let mut rows: Vec<CompactionRow> = sessions
+        .into_values()
+        .filter(|row| row.compactions > 0)
+        .collect();
+    for row in &rows {
+        total.compactions += row.compactions;
+        total.manual += row.manual;
+        total.summarized_tokens += row.summarized_tokens;
+        total.summary_cost = total.summary_cost.zip(row.summary_cost).map(|(a, b)| a + b);
+        total.session_cost = total.session_cost.zip(row.session_cost).map(|(a, b)| a + b);
+    }
+
+    rows.sort_by(|a, b| {
+        b.compactions
+            .cmp(&a.compactions)
+            .then(b.summarized_tokens.cmp(&a.summarized_tokens))
+            .then(a.session.cmp(&b.session))
+    });
+
+    CompactionReport { rows, total }
+}
@@ -1,16 +1,47 @@
 use std::fs;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 
+use crate::cost::CostBasis;
 use crate::paths;
 use crate::pricing::PricingSource;
+use crate::project::ProjectNaming;
 
 #[derive(Debug, Deserialize, Default)]
 pub struct Config {
     pub pricing_source: Option<PricingSource>,
+    pub cost_basis: Option<CostBasis>,
     pub currency: Option<String>,
     #[serde(default)]
     pub spawn: Option<SpawnConfig>,
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    #[serde(default)]
+    pub projects: ProjectsConfig,
+}
+
+/// How working directories are grouped into projects (`[projects]`).
+#[derive(Debug, Deserialize, Default)]
+pub struct ProjectsConfig {
+    /// `dir` (repository directory name, the default) or `remote`
+    /// (`owner/repo` from the `origin` remote).
+    #[serde(default)]
+    pub name: ProjectNaming,
+}
+
+/// Prices that override the pricing source (`[pricing]`).
+#[derive(Debug, Deserialize, Default)]
+pub struct PricingConfig {
+    /// USD per server-side web search. Default: the source's rate, else $0.01.
+    pub web_search_cost_per_request: Option<f64>,
+    /// USD per server-side web fetch. Default: free.
+    pub web_fetch_cost_per_request: Option<f64>,
+    /// Scale on standard prices for priority-tier requests when the source
+    /// has no priority prices for the model. Default: the standard rate.
+    pub priority_multiplier: Option<f64>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -18,6 +49,82 @@ pub struct SpawnConfig {
     pub ephemeral: Option<bool>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct ProvidersConfig {
+    /// Directories searched for per-repo session files (Aider chat history
+    /// and friends). Empty means the built-in defaults under `$HOME`.
+    #[serde(default)]
+    pub code_roots: Vec<PathBuf>,
+    #[serde(default)]
+    pub aider: AiderConfig,
+    /// Config-defined JSONL tools (`[[providers.custom]]`).
+    #[serde(default)]
+    pub custom: Vec<CustomProviderConfig>,
+    /// External commands that print usage records (`[[providers.plugin]]`).
+    #[serde(default)]
+    pub plugin: Vec<PluginConfig>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AiderConfig {
+    /// Analytics logs written via aider's `--analytics-log`.
+    #[serde(default)]
+    pub analytics_logs: Vec<PathBuf>,
+}
+
+/// A JSONL source described entirely in config. Field mappings are JSON
+/// pointers (RFC 6901, e.g. `/message/usage/input_tokens`) into each line.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CustomProviderConfig {
+    /// Tool name shown in reports and accepted by `--tool`.
+    pub name: String,
+    /// Glob matching the session files, e.g. `~/.mytool/sessions/**/*.jsonl`.
+    pub root: String,
+    /// Substring a line must contain to be parsed at all.
+    #[serde(default)]
+    pub filter: String,
+    /// RFC 3339 string, or unix seconds/milliseconds.
+    pub timestamp: String,
+    pub model: Option<String>,
+    /// Defaults to the file stem.
+    pub session: Option<String>,
+    /// Defaults to the tool name.
+    pub project: Option<String>,
+    /// Defaults to a key built from session, timestamp and token counts.
+    pub message_id: Option<String>,
+    pub input_tokens: Option<String>,
+    pub output_tokens: Option<String>,
+    pub cache_creation_input_tokens: Option<String>,
+    pub cache_read_input_tokens: Option<String>,
+    pub reasoning_tokens: Option<String>,
+    /// Cost in USD the tool recorded, if it logs one.
+    pub cost: Option<String>,
+}
+
+/// An executable that prints `UsageRecord`-shaped NDJSON on stdout.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PluginConfig {
+    /// Executable name (looked up on `PATH`) or path.
+    pub command: PathBuf,
+    /// Tool name; defaults to the executable name minus `tku-provider-`.
+    pub name: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds before the plugin is killed. Defaults to 60.
+    pub timeout_secs: Option<u64>,
+}
+
+/// Expand a leading `~/` against `$HOME`. Config paths are user-written, so
+/// the shell-style shorthand is expected to work.
+pub fn expand_home(path: &std::path::Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    path.to_path_buf()
+}
+
 pub fn load_config() -> Config {
     let Some(path) = paths::config_file() else {
         return Config::default();
@@ -1,5 +1,8 @@
 use std::collections::HashSet;
 
+use clap::ValueEnum;
+use serde::Deserialize;
+
 use crate::types::UsageRecord;
 
 /// Per-token pricing for a model.
@@ -9,23 +12,140 @@ pub struct ModelPricing {
     pub output_cost_per_token: f64,
     pub cache_read_input_token_cost: Option<f64>,
     pub cache_creation_input_token_cost: Option<f64>,
+    /// Rate for cache writes with the 1-hour TTL.
+    pub cache_creation_input_token_cost_above_1hr: Option<f64>,
+    /// Rate for reasoning tokens, where it differs from output.
+    pub output_cost_per_reasoning_token: Option<f64>,
+    pub web_search_cost_per_request: Option<f64>,
+    pub web_fetch_cost_per_request: Option<f64>,
+    /// Prices for non-standard service tiers, where the source lists them.
+    pub tiers: Vec<TierPricing>,
+}
+
+/// Per-token pricing for one service tier of a model.
+#[derive(Debug, Clone)]
+pub struct TierPricing {
+    /// Normalized tier name, as in `UsageRecord::service_tier`.
+    pub tier: &'static str,
+    pub input_cost_per_token: f64,
+    pub output_cost_per_token: f64,
+    pub cache_read_input_token_cost: Option<f64>,
+}
+
+/// Scale applied to standard prices for a tier the source has no prices
+/// for. Flex and batch are half price at both Anthropic and OpenAI; there's
+/// no general rule for priority, so it takes the configured multiplier and
+/// stays at the standard rate without one.
+fn tier_multiplier(tier: &str, priority: Option<f64>) -> f64 {
+    match tier {
+        "flex" | "batch" => 0.5,
+        "priority" => priority.unwrap_or(1.0),
+        _ => 1.0,
+    }
+}
+
+/// Anthropic's web search rate ($10 per 1,000 searches), used when the
+/// pricing source doesn't publish one. Only Claude records carry searches.
+const DEFAULT_WEB_SEARCH_COST: f64 = 0.01;
+
+/// Which cost reports use for each record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CostBasis {
+    /// Tokens priced from the pricing source
+    #[default]
+    Computed,
+    /// Only the cost the tool itself recorded
+    Reported,
+    /// The recorded cost where there is one, computed otherwise
+    PreferReported,
 }
 
 /// Trait for looking up pricing by model name.
 pub trait PricingMap {
     fn get(&self, model: &str) -> Option<&ModelPricing>;
 
+    fn basis(&self) -> CostBasis {
+        CostBasis::Computed
+    }
+
+    /// Scale on standard prices for priority requests to models the source
+    /// has no priority prices for (`[pricing] priority_multiplier`).
+    fn priority_multiplier(&self) -> Option<f64> {
+        None
+    }
+
+    /// Whether a priority request was priced at the standard rate, for lack
+    /// of both source prices and a configured multiplier.
+    fn priority_at_standard_rate(&self, r: &UsageRecord) -> bool {
+        r.service_tier.as_deref() == Some("priority")
+            && self.priority_multiplier().is_none()
+            && self
+                .get(&r.model)
+                .is_some_and(|p| !p.tiers.iter().any(|t| t.tier == "priority"))
+    }
+
+    /// The record's cost under `basis()`.
     fn cost_for_record(&self, r: &UsageRecord) -> Option<f64> {
+        match self.basis() {
+            CostBasis::Computed => self.computed_cost(r),
+            CostBasis::Reported => r.reported_cost,
+            CostBasis::PreferReported => r.reported_cost.or_else(|| self.computed_cost(r)),
+        }
+    }
+
+    /// Reported minus computed cost, for records that have both.
+    fn cost_discrepancy(&self, r: &UsageRecord) -> Option<f64> {
+        Some(r.reported_cost? - self.computed_cost(r)?)
+    }
+
+    fn computed_cost(&self, r: &UsageRecord) -> Option<f64> {
         let p = self.get(&r.model)?;
+        let tier = r.service_tier.as_deref().unwrap_or("standard");
+        let scale = tier_multiplier(tier, self.priority_multiplier());
+        let tier_rates = p.tiers.iter().find(|t| t.tier == tier);
+
         let mut cost = 0.0;
-        cost += r.input_tokens as f64 * p.input_cost_per_token;
-        cost += r.output_tokens as f64 * p.output_cost_per_token;
-        if let Some(cr) = p.cache_read_input_token_cost {
+        cost += r.input_tokens as f64
+            * tier_rates.map_or(p.input_cost_per_token * scale, |t| t.input_cost_per_token);
+        let output_rate =
+            tier_rates.map_or(p.output_cost_per_token * scale, |t| t.output_cost_per_token);
+        cost += r.output_tokens as f64 * output_rate;
+        let reasoning_rate = match (tier_rates, p.output_cost_per_reasoning_token) {
+            (_, None) => output_rate,
+            (None, Some(c)) => c * scale,
+            // Tier prices don't list reasoning; keep its ratio to output.
+            (Some(t), Some(c)) if p.output_cost_per_token > 0.0 => {
+                c * t.output_cost_per_token / p.output_cost_per_token
+            }
+            (Some(t), Some(_)) => t.output_cost_per_token,
+        };
+        cost += r.reasoning_tokens as f64 * reasoning_rate;
+        if let Some(cr) = tier_rates
+            .and_then(|t| t.cache_read_input_token_cost)
+            .or(p.cache_read_input_token_cost.map(|c| c * scale))
+        {
             cost += r.cache_read_input_tokens as f64 * cr;
         }
+        let cache_write_1h = r
+            .cache_creation_1h_input_tokens
+            .min(r.cache_creation_input_tokens);
         if let Some(cc) = p.cache_creation_input_token_cost {
-            cost += r.cache_creation_input_tokens as f64 * cc;
+            cost += (r.cache_creation_input_tokens - cache_write_1h) as f64 * cc * scale;
         }
+        // Anthropic bills 1h writes at twice the base input rate. A source
+        // that doesn't price cache writes at all leaves both TTLs unpriced.
+        if let Some(cc_1h) = p.cache_creation_input_token_cost_above_1hr.or(p
+            .cache_creation_input_token_cost
+            .map(|_| 2.0 * p.input_cost_per_token))
+        {
+            cost += cache_write_1h as f64 * cc_1h * scale;
+        }
+        cost += r.web_search_requests as f64
+            * p.web_search_cost_per_request
+                .unwrap_or(DEFAULT_WEB_SEARCH_COST);
+        // Web fetch has no per-request fee unless configured otherwise.
+        cost += r.web_fetch_requests as f64 * p.web_fetch_cost_per_request.unwrap_or(0.0);
         Some(cost)
     }
 
@@ -36,7 +156,12 @@ pub trait PricingMap {
     /// On warm runs the priced-model subset dominates and this keeps the
     /// allocation count tiny.
     fn unpriced_models(&self, records: &[UsageRecord]) -> Vec<String> {
-        let distinct: HashSet<&str> = records.iter().map(|r| r.model.as_str()).collect();
+        // Failure markers have no tokens to price.
+        let distinct: HashSet<&str> = records
+            .iter()
+            .filter(|r| !r.is_failure())
+            .map(|r| r.model.as_str())
+            .collect();
         let mut models: Vec<String> = distinct
             .into_iter()
             .filter(|m| self.get(m).is_none())
@@ -73,7 +198,19 @@ mod tests {
             output_tokens: 1,
             cache_creation_input_tokens: 0,
             cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
             account_uuid: None,
+            reported_cost: None,
         }
     }
 
@@ -85,6 +222,11 @@ mod tests {
                 output_cost_per_token: 0.0,
                 cache_read_input_token_cost: None,
                 cache_creation_input_token_cost: None,
+                cache_creation_input_token_cost_above_1hr: None,
+                output_cost_per_reasoning_token: None,
+                web_search_cost_per_request: None,
+                web_fetch_cost_per_request: None,
+                tiers: Vec::new(),
             },
         )
     }
@@ -107,4 +249,196 @@ mod tests {
         let out = pricing.unpriced_models(&records);
         assert_eq!(out, vec!["alpha".to_string(), "beta".into(), "zeta".into()]);
     }
+
+    #[test]
+    fn one_hour_cache_writes_use_their_own_rate() {
+        let (k, mut v) = priced("m");
+        v.input_cost_per_token = 1.0;
+        v.output_cost_per_token = 0.0;
+        v.cache_creation_input_token_cost = Some(1.25);
+        let mut r = rec("m");
+        r.input_tokens = 0;
+        r.output_tokens = 0;
+        r.cache_creation_input_tokens = 10;
+        r.cache_creation_1h_input_tokens = 4;
+
+        let with_rate = {
+            let mut v = v.clone();
+            v.cache_creation_input_token_cost_above_1hr = Some(3.0);
+            TestPricing(HashMap::from([(k.clone(), v)]))
+        };
+        assert_eq!(with_rate.cost_for_record(&r), Some(6.0 * 1.25 + 4.0 * 3.0));
+
+        // Without a published 1h rate, fall back to twice the input rate.
+        let fallback = TestPricing(HashMap::from([(k.clone(), v.clone())]));
+        assert_eq!(fallback.cost_for_record(&r), Some(6.0 * 1.25 + 4.0 * 2.0));
+
+        // No cache-write price at all: 1h writes are free like 5m ones.
+        v.cache_creation_input_token_cost = None;
+        let unpriced = TestPricing(HashMap::from([(k, v)]));
+        assert_eq!(unpriced.cost_for_record(&r), Some(0.0));
+    }
+
+    #[test]
+    fn web_searches_are_billed_per_request() {
+        let (k, mut v) = priced("m");
+        let mut r = rec("m");
+        r.web_search_requests = 3;
+        r.web_fetch_requests = 5;
+
+        let fallback = TestPricing(HashMap::from([(k.clone(), v.clone())]));
+        assert_eq!(fallback.cost_for_record(&r), Some(0.03));
+
+        v.web_search_cost_per_request = Some(0.02);
+        v.web_fetch_cost_per_request = Some(0.001);
+        let priced = TestPricing(HashMap::from([(k, v)]));
+        let cost = priced.cost_for_record(&r).unwrap();
+        assert!((cost - 0.065).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reasoning_is_priced_as_output_unless_listed() {
+        let (k, mut v) = priced("m");
+        v.output_cost_per_token = 2.0;
+        let mut r = rec("m");
+        r.input_tokens = 0;
+        r.output_tokens = 1;
+        r.reasoning_tokens = 3;
+
+        let as_output = TestPricing(HashMap::from([(k.clone(), v.clone())]));
+        assert_eq!(as_output.cost_for_record(&r), Some(8.0));
+
+        v.output_cost_per_reasoning_token = Some(1.0);
+        let listed = TestPricing(HashMap::from([(k, v)]));
+        assert_eq!(listed.cost_for_record(&r), Some(5.0));
+    }
+
+    #[test]
+    fn service_tier_uses_tier_prices_or_multiplier() {
+        let (k, mut v) = priced("m");
+        v.input_cost_per_token = 2.0;
+        v.tiers.push(TierPricing {
+            tier: "priority",
+            input_cost_per_token: 5.0,
+            output_cost_per_token: 0.0,
+            cache_read_input_token_cost: None,
+        });
+        let pricing = TestPricing(HashMap::from([(k, v)]));
+        let mut r = rec("m");
+        r.output_tokens = 0;
+
+        assert_eq!(pricing.cost_for_record(&r), Some(2.0));
+        r.service_tier = Some("priority".into());
+        assert_eq!(pricing.cost_for_record(&r), Some(5.0));
+        r.service_tier = Some("flex".into());
+        assert_eq!(pricing.cost_for_record(&r), Some(1.0));
+    }
+
+    #[test]
+    fn reasoning_follows_tier_prices() {
+        let (k, mut v) = priced("m");
+        v.output_cost_per_token = 2.0;
+        v.tiers.push(TierPricing {
+            tier: "priority",
+            input_cost_per_token: 0.0,
+            output_cost_per_token: 4.0,
+            cache_read_input_token_cost: None,
+        });
+        let mut r = rec("m");
+        r.input_tokens = 0;
+        r.output_tokens = 0;
+        r.reasoning_tokens = 1;
+        r.service_tier = Some("priority".into());
+
+        let as_output = TestPricing(HashMap::from([(k.clone(), v.clone())]));
+        assert_eq!(as_output.cost_for_record(&r), Some(4.0));
+
+        // A separate reasoning rate keeps its ratio to output on the tier.
+        v.output_cost_per_reasoning_token = Some(1.0);
+        let listed = TestPricing(HashMap::from([(k, v)]));
+        assert_eq!(listed.cost_for_record(&r), Some(2.0));
+        r.service_tier = Some("flex".into());
+        assert_eq!(listed.cost_for_record(&r), Some(0.5));
+    }
+
+    struct PriorityPricing(TestPricing, Option<f64>);
+    impl PricingMap for PriorityPricing {
+        fn get(&self, model: &str) -> Option<&ModelPricing> {
+            self.0.get(model)
+        }
+        fn priority_multiplier(&self) -> Option<f64> {
+            self.1
+        }
+    }
+
+    #[test]
+    fn priority_without_source_prices_uses_configured_multiplier() {
+        let (k, mut v) = priced("m");
+        v.input_cost_per_token = 2.0;
+        let pricing = |multiplier| {
+            PriorityPricing(
+                TestPricing(HashMap::from([(k.clone(), v.clone())])),
+                multiplier,
+            )
+        };
+        let mut r = rec("m");
+        r.output_tokens = 0;
+        r.service_tier = Some("priority".into());
+
+        let standard = pricing(None);
+        assert_eq!(standard.cost_for_record(&r), Some(2.0));
+        assert!(standard.priority_at_standard_rate(&r));
+
+        let scaled = pricing(Some(1.8));
+        assert_eq!(scaled.cost_for_record(&r), Some(3.6));
+        assert!(!scaled.priority_at_standard_rate(&r));
+
+        r.service_tier = Some("flex".into());
+        assert_eq!(scaled.cost_for_record(&r), Some(1.0));
+        assert!(!standard.priority_at_standard_rate(&r));
+    }
+
+    #[test]
+    fn service_tier_names_are_normalized() {
+        use crate::types::normalize_service_tier;
+        assert_eq!(normalize_service_tier("default"), "standard");
+        assert_eq!(normalize_service_tier("Priority"), "priority");
+        assert_eq!(normalize_service_tier("fast"), "priority");
+        assert_eq!(normalize_service_tier("flex"), "flex");
+    }
+
+    struct BasisPricing(TestPricing, CostBasis);
+    impl PricingMap for BasisPricing {
+        fn get(&self, model: &str) -> Option<&ModelPricing> {
+            self.0.get(model)
+        }
+        fn basis(&self) -> CostBasis {
+            self.1
+        }
+    }
+
+    #[test]
+    fn cost_basis_picks_reported_or_computed() {
+        let (k, mut v) = priced("m");
+        v.input_cost_per_token = 1.0;
+        let pricing =
+            |basis| BasisPricing(TestPricing(HashMap::from([(k.clone(), v.clone())])), basis);
+
+        let mut reported = rec("m");
+        reported.reported_cost = Some(3.0);
+        let computed_only = rec("m");
+
+        let computed = pricing(CostBasis::Computed);
+        assert_eq!(computed.cost_for_record(&reported), Some(1.0));
+        assert_eq!(computed.cost_discrepancy(&reported), Some(2.0));
+        assert_eq!(computed.cost_discrepancy(&computed_only), None);
+
+        let only_reported = pricing(CostBasis::Reported);
+        assert_eq!(only_reported.cost_for_record(&reported), Some(3.0));
+        assert_eq!(only_reported.cost_for_record(&computed_only), None);
+
+        let prefer = pricing(CostBasis::PreferReported);
+        assert_eq!(prefer.cost_for_record(&reported), Some(3.0));
+        assert_eq!(prefer.cost_for_record(&computed_only), Some(1.0));
+    }
 }
@@ -1,5 +1,5 @@
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
 use crate::types::{Provider, UsageRecord};
@@ -31,9 +31,24 @@ fn fingerprint(
     h.finish()
 }
 
+/// How much an `agent` label says about where a record came from.
+///
+/// Claude reports each subagent response twice: through the parent
+/// transcript's `agent_progress` lines, labelled with the Task's subagent
+/// type, and in the subagent's own transcript, which only knows it's a
+/// `subagent`. Duplicates keep the more specific label, so attribution
+/// doesn't depend on which file was scanned first.
+fn agent_rank(agent: Option<&str>) -> u8 {
+    match agent {
+        None => 0,
+        Some("subagent") => 1,
+        Some(_) => 2,
+    }
+}
+
 pub fn dedup(records: Vec<UsageRecord>) -> Vec<UsageRecord> {
-    let mut seen: HashSet<u64> = HashSet::with_capacity(records.len());
-    let mut out = Vec::with_capacity(records.len());
+    let mut seen: HashMap<u64, usize> = HashMap::with_capacity(records.len());
+    let mut out: Vec<UsageRecord> = Vec::with_capacity(records.len());
     for r in records {
         let key = fingerprint(
             r.provider,
@@ -41,8 +56,17 @@ pub fn dedup(records: Vec<UsageRecord>) -> Vec<UsageRecord> {
             &r.request_id,
             r.account_uuid.as_deref(),
         );
-        if seen.insert(key) {
-            out.push(r);
+        match seen.entry(key) {
+            Entry::Vacant(e) => {
+                e.insert(out.len());
+                out.push(r);
+            }
+            Entry::Occupied(e) => {
+                let kept = &mut out[*e.get()];
+                if agent_rank(r.agent.as_deref()) > agent_rank(kept.agent.as_deref()) {
+                    kept.agent = r.agent;
+                }
+            }
         }
     }
     out
@@ -66,7 +90,19 @@ mod tests {
             output_tokens: 0,
             cache_creation_input_tokens: 0,
             cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
             account_uuid: None,
+            reported_cost: None,
         }
     }
 
@@ -135,4 +171,21 @@ mod tests {
         ]);
         assert_eq!(out.len(), 1);
     }
+
+    #[test]
+    fn subagent_copies_keep_the_task_agent_type_in_either_order() {
+        let labelled = |agent: &str| {
+            let mut r = rec(Provider::Claude, "m1", "r1");
+            r.agent = Some(agent.to_string());
+            r
+        };
+        for records in [
+            vec![labelled("subagent"), labelled("Explore")],
+            vec![labelled("Explore"), labelled("subagent")],
+        ] {
+            let out = dedup(records);
+            assert_eq!(out.len(), 1);
+            assert_eq!(out[0].agent.as_deref(), Some("Explore"));
+        }
+    }
 }
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use crate::aggregate::bucket_key;
+use crate::burn::record_tokens;
+use crate::cli::{Command, ErrorGrouping};
+use crate::cost::PricingMap;
+use crate::types::UsageRecord;
+
+#[derive(Default)]
+pub struct ErrorRow {
+    pub key: String,
+    /// Every failed API call, overloaded and rate-limited ones included.
+    pub api_errors: u64,
+    pub overloaded: u64,
+    pub rate_limited: u64,
+    pub interrupted: u64,
+    /// Tokens used by turns that ended in one of the above.
+    pub lost_tokens: u64,
+    /// None when any of those tokens are unpriced.
+    pub lost_cost: Option<f64>,
+}
+
+pub struct ErrorReport {
+    pub rows: Vec<ErrorRow>,
+    pub total: ErrorRow,
+}
+
+impl ErrorRow {
+    fn add(&mut self, r: &UsageRecord, pricing: &dyn PricingMap) {
+        match r.event.as_deref() {
+            Some("interrupted") => self.interrupted += 1,
+            Some(kind @ ("api_error" | "overloaded" | "rate_limited")) => {
+                self.api_errors += 1;
+                match kind {
+                    "overloaded" => self.overloaded += 1,
+                    "rate_limited" => self.rate_limited += 1,
+                    _ => {}
+                }
+            }
+            Some(_) => {}
+            None if r.failed_turn => {
+                self.lost_tokens += record_tokens(r);
+                self.lost_cost = match (self.lost_cost, pricing.cost_for_record(r)) {
+                    (Some(acc), Some(c)) => Some(acc + c),
+                    _ => None,
+                };
+            }
+            None => {}
+        }
+    }
+}
+
+/// Failures and the usage they wasted, grouped by day, session or model.
+/// Records that are neither failures nor part of a failed turn add nothing.
+pub fn compute(
+    records: &[UsageRecord],
+    pricing: &dyn PricingMap,
+    by: &ErrorGrouping,
+) -> ErrorReport {
+    let new_row = |key: String| ErrorRow {
+        key,
+        lost_cost: Some(0.0),
+        ..ErrorRow::default()
+    };
+
+    // Same row keys as the matching usage reports.
+    let mode = match by {
+        ErrorGrouping::Day => Command::Daily,
+        ErrorGrouping::Session => Command::Session { by_agent: false },
+        ErrorGrouping::Model => Command::Model,
+    };
+
+    let mut rows: HashMap<String, ErrorRow> = HashMap::new();
+    let mut total = new_row("TOTAL".to_string());
+    for r in records {
+        let key = bucket_key(r, &mode);
+        rows.entry(key.clone())
+            .or_insert_with(|| new_row(key))
+            .add(r, pricing);
+        total.add(r, pricing);
+    }
+
+    let mut rows: Vec<ErrorRow> = rows.into_values().collect();
+    match by {
+        ErrorGrouping::Day => rows.sort_by(|a, b| a.key.cmp(&b.key)),
+        _ => rows.sort_by(|a, b| {
+            let failures = |r: &ErrorRow| r.api_errors + r.interrupted;
+            failures(b)
+                .cmp(&failures(a))
+                .then(b.lost_tokens.cmp(&a.lost_tokens))
+                .then(a.key.cmp(&b.key))
+        }),
+    }
+
+    ErrorReport { rows, total }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::ModelPricing;
+    use chrono::{DateTime, Utc};
+
+    /// Prices model `m` at 1 per input token; anything else is unpriced.
+    struct TestPricing(ModelPricing);
+    impl PricingMap for TestPricing {
+        fn get(&self, model: &str) -> Option<&ModelPricing> {
+            (model == "m").then_some(&self.0)
+        }
+    }
+
+    fn pricing() -> TestPricing {
+        TestPricing(ModelPricing {
+            input_cost_per_token: 1.0,
+            output_cost_per_token: 0.0,
+            cache_read_input_token_cost: None,
+            cache_creation_input_token_cost: None,
+            cache_creation_input_token_cost_above_1hr: None,
+            output_cost_per_reasoning_token: None,
+            web_search_cost_per_request: None,
+            web_fetch_cost_per_request: None,
+            tiers: Vec::new(),
+        })
+    }
+
+    fn rec(day: &str, session: &str, model: &str, event: Option<&str>) -> UsageRecord {
+        let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339(&format!("{day}T12:00:00Z"))
+            .unwrap()
+            .with_timezone(&Utc);
+        UsageRecord {
+            provider: crate::types::Provider::Claude,
+            session_id: session.into(),
+            timestamp,
+            project: "proj".into(),
+            model: model.into(),
+            message_id: "m".into(),
+            request_id: "r".into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: event.map(String::from),
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: None,
+        }
+    }
+
+    fn lost(day: &str, session: &str, model: &str, input: u64) -> UsageRecord {
+        let mut r = rec(day, session, model, None);
+        r.input_tokens = input;
+        r.failed_turn = true;
+        r
+    }
+
+    fn row<'a>(report: &'a ErrorReport, key: &str) -> &'a ErrorRow {
+        report.rows.iter().find(|r| r.key == key).unwrap()
+    }
+
+    #[test]
+    fn failures_are_counted_by_kind() {
+        let records = vec![
+            rec("2026-01-01", "s1", "m", Some("api_error")),
+            rec("2026-01-01", "s1", "m", Some("overloaded")),
+            rec("2026-01-01", "s1", "m", Some("rate_limited")),
+            rec("2026-01-01", "s1", "m", Some("rate_limited")),
+            rec("2026-01-01", "s1", "m", Some("interrupted")),
+            rec("2026-01-01", "s1", "m", Some("auto_compact")),
+            rec("2026-01-01", "s1", "m", None),
+        ];
+        let report = compute(&records, &pricing(), &ErrorGrouping::Day);
+
+        let total = &report.total;
+        assert_eq!(total.api_errors, 4, "overloaded and rate-limited count too");
+        assert_eq!((total.overloaded, total.rate_limited), (1, 2));
+        assert_eq!(total.interrupted, 1);
+        assert_eq!(total.lost_tokens, 0);
+        assert_eq!(total.lost_cost, Some(0.0));
+    }
+
+    #[test]
+    fn failed_turns_lose_their_tokens_and_cost() {
+        let records = vec![
+            lost("2026-01-01", "s1", "m", 30),
+            lost("2026-01-01", "s1", "m", 12),
+            rec("2026-01-01", "s1", "m", None),
+            lost("2026-01-02", "s2", "unpriced", 5),
+        ];
+        let report = compute(&records, &pricing(), &ErrorGrouping::Day);
+
+        let first = row(&report, "2026-01-01");
+        assert_eq!(first.lost_tokens, 42);
+        assert_eq!(first.lost_cost, Some(42.0));
+        let second = row(&report, "2026-01-02");
+        assert_eq!(second.lost_tokens, 5);
+        assert_eq!(
+            second.lost_cost, None,
+            "unpriced tokens leave the cost unknown"
+        );
+        assert_eq!(report.total.lost_tokens, 47);
+        assert_eq!(report.total.lost_cost, None);
+    }
+
+    #[test]
+    fn rows_group_by_day_session_or_model() {
+        let records = vec![
+            rec("2026-01-02", "s1", "m", Some("api_error")),
+            rec("2026-01-01", "s2", "other", Some("interrupted")),
+            rec("2026-01-01", "s2", "m", Some("overloaded")),
+            lost("2026-01-01", "s2", "m", 7),
+        ];
+
+        let by_day = compute(&records, &pricing(), &ErrorGrouping::Day);
+        let keys: Vec<&str> = by_day.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, ["2026-01-01", "2026-01-02"], "days sort by date");
+
+        let by_session = compute(&records, &pricing(), &ErrorGrouping::Session);
+        let keys: Vec<&str> = by_session.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, ["proj | s2", "proj | s1"], "most failures first");
+        assert_eq!(row(&by_session, "proj | s2").lost_tokens, 7);
+
+        let by_model = compute(&records, &pricing(), &ErrorGrouping::Model);
+        let m = row(&by_model, "m");
+        assert_eq!((m.api_errors, m.overloaded, m.lost_tokens), (2, 1, 7));
+        assert_eq!(row(&by_model, "other").interrupted, 1);
+        assert_eq!(by_model.total.api_errors, 2);
+    }
+}
@@ -147,7 +147,11 @@ fn build_buckets(period: &GraphPeriod, relative: bool) -> BucketSpec {
 }
 
 fn total_tokens(r: &UsageRecord) -> u64 {
-    r.input_tokens + r.output_tokens + r.cache_creation_input_tokens + r.cache_read_input_tokens
+    r.input_tokens
+        + r.output_tokens
+        + r.reasoning_tokens
+        + r.cache_creation_input_tokens
+        + r.cache_read_input_tokens
 }
 
 pub fn render(records: &[UsageRecord], period: &GraphPeriod, relative: bool) -> Result<()> {
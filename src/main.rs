@@ -3,19 +3,23 @@ mod aggregate;
 mod atomic_write;
 mod burn;
 mod cli;
+mod compaction;
 mod config;
 mod cost;
 mod dedup;
+mod errors;
 mod exchange;
 mod graph;
 mod http;
 mod output;
 mod paths;
 mod pricing;
+mod project;
 mod providers;
 mod spawn;
 mod storage;
 mod subscription;
+mod tools;
 mod types;
 mod watch;
 
@@ -85,6 +89,12 @@ fn matches_account(
         .unwrap_or(false)
 }
 
+/// `--branch`: an exact match on the record's git branch. Records from
+/// tools that don't log a branch never match.
+fn matches_branch(record: &types::UsageRecord, branch: Option<&str>) -> bool {
+    branch.is_none_or(|b| record.git_branch.as_deref() == Some(b))
+}
+
 fn handle_account(action: &cli::AccountAction) -> Result<()> {
     match action {
         cli::AccountAction::Add { name } => accounts::add(name),
@@ -114,7 +124,20 @@ fn main() -> Result<()> {
     if let cli::Command::Account { action } = &mode {
         return handle_account(action);
     }
+    if let cli::Command::ImportUsage { file } = &mode {
+        let (rows, already) = providers::import::import_usage(cli.import_source()?, file)?;
+        if already {
+            println!(
+                "{} was already imported ({rows} usage rows)",
+                file.display()
+            );
+        } else {
+            println!("Imported {rows} usage rows from {}", file.display());
+        }
+        return Ok(());
+    }
 
+    let from = cli.start_date()?;
     let config = config::load_config();
 
     // Merge: CLI > config > default
@@ -124,6 +147,8 @@ fn main() -> Result<()> {
         .or(config.pricing_source)
         .unwrap_or_default();
 
+    let cost_basis = cli.cost_basis.or(config.cost_basis).unwrap_or_default();
+
     let currency = cli
         .currency
         .clone()
@@ -153,16 +178,16 @@ fn main() -> Result<()> {
     } else if let cli::Command::Bar { ref period, .. } = mode {
         Some(bar_date_range(period))
     } else if matches!(mode, cli::Command::Subscription { .. })
-        && cli.from.is_none()
+        && from.is_none()
         && cli.to.is_none()
     {
         let today = chrono::Local::now().date_naive();
         Some((today - chrono::Duration::days(35), today))
-    } else if matches!(mode, cli::Command::Watch { .. }) && cli.from.is_none() && cli.to.is_none() {
+    } else if matches!(mode, cli::Command::Watch { .. }) && from.is_none() && cli.to.is_none() {
         let today = chrono::Local::now().date_naive();
         Some((today, today))
     } else {
-        match (cli.from, cli.to) {
+        match (from, cli.to) {
             (Some(f), Some(t)) => Some((f, t)),
             (Some(f), None) => Some((f, chrono::Utc::now().date_naive())),
             (None, Some(t)) => Some((chrono::NaiveDate::from_ymd_opt(2020, 1, 1).unwrap_or(t), t)),
@@ -171,7 +196,15 @@ fn main() -> Result<()> {
     };
 
     if let cli::Command::Watch { full, interval } = mode {
-        return watch::run(full, interval, &cli, &pricing_source, &currency, date_range);
+        return watch::run(
+            full,
+            interval,
+            &cli,
+            &pricing_source,
+            cost_basis,
+            &currency,
+            date_range,
+        );
     }
 
     // Pre-scan: detect implicit credential swap so the registry's switch log
@@ -213,7 +246,16 @@ fn main() -> Result<()> {
     store.flush();
     let all_records = store.drain_all();
 
-    let records = dedup::dedup(all_records);
+    let mut records = dedup::dedup(all_records);
+    project::label(&mut records, config.projects.name);
+    // Event markers aren't usage; only `tku errors` reads the failures,
+    // along with the usage of the turns they ended, and `tku compactions`
+    // the compactions.
+    match mode {
+        cli::Command::Errors { .. } => records.retain(|r| r.is_failure() || r.failed_turn),
+        cli::Command::Compactions => records.retain(|r| r.event.is_none() || r.is_compaction()),
+        _ => records.retain(|r| r.event.is_none()),
+    }
 
     // Post-scan bootstrap: register the active credentials as "default" on
     // first run. Implicit-swap detection already ran pre-scan; this only
@@ -236,7 +278,20 @@ fn main() -> Result<()> {
         if all && account_filter.is_some() {
             bail!("`--all` and `--account` are mutually exclusive");
         }
+        match cli.tool.as_deref().map(Provider::from_str) {
+            None | Some(Ok(Provider::Claude)) => {}
+            Some(Ok(Provider::Codex)) => {
+                if plan || all || live || account_filter.is_some() {
+                    bail!("`--plan`, `--all`, `--live` and `--account` only apply to Claude");
+                }
+                let exchange = exchange::load_exchange_rate(&currency, cli.offline);
+                let pricing = pricing::load_pricing(&pricing_source, cli.offline)?;
+                return subscription::run_codex(&exchange, &records, &pricing);
+            }
+            Some(_) => bail!("`tku sub` supports --tool claude (default) or codex"),
+        }
         let exchange = exchange::load_exchange_rate(&currency, cli.offline);
+        // Plan value is measured at API list prices; --cost-basis doesn't apply.
         let pricing = pricing::load_pricing(&pricing_source, cli.offline)?;
         if all {
             return subscription::run_all(&exchange, &records, &pricing, cli.offline, live);
@@ -258,17 +313,10 @@ fn main() -> Result<()> {
     }
 
     let proj_needle = cli.project.as_ref().map(|p| p.to_lowercase());
-    // Parse the `--tool` string into a typed Provider up front. Three states:
-    //   None           → no --tool flag, keep all records
-    //   Some(Ok(p))    → filter to this provider
-    //   Some(Err(()))  → --tool was set but name doesn't map to a Provider,
-    //                    so no records should match (loud-fail alternative
-    //                    would be bail! here; we keep the pre-refactor
-    //                    silent-drop semantics).
-    let tool_needle: Option<Result<Provider, ()>> = cli
-        .tool
-        .as_ref()
-        .map(|t| Provider::from_str(t).map_err(|_| ()));
+    // Parse the `--tool` string into a typed Provider up front. Custom tools
+    // and plugins registered their names while scanning, so anything that
+    // still doesn't parse is a typo.
+    let tool_needle: Option<Provider> = cli.tool.as_deref().map(Provider::from_str).transpose()?;
 
     let records: Vec<_> = records
         .into_iter()
@@ -279,15 +327,13 @@ fn main() -> Result<()> {
             }
             None => true,
         })
-        .filter(|r| match &proj_needle {
-            Some(needle) => r.project.to_lowercase().contains(needle),
-            None => true,
-        })
-        .filter(|r| match tool_needle {
-            None => true,
-            Some(Ok(needle)) => r.provider == needle,
-            Some(Err(())) => false,
+        .filter(|r| {
+            proj_needle
+                .as_deref()
+                .is_none_or(|needle| project::matches(r, needle))
         })
+        .filter(|r| tool_needle.is_none_or(|needle| r.provider == needle))
+        .filter(|r| matches_branch(r, cli.branch.as_deref()))
         .filter(|r| matches_account(r, account_filter.as_deref(), &account_registry))
         .collect();
 
@@ -320,7 +366,7 @@ fn main() -> Result<()> {
             return Ok(());
         }
 
-        let pricing = pricing::load_pricing(&pricing_source, cli.offline)?;
+        let pricing = pricing::load_pricing(&pricing_source, cli.offline)?.with_basis(cost_basis);
         let buckets = aggregate::aggregate(&records, &mode, &pricing);
         let bucket = buckets.values().next();
         output::print_bar(
@@ -341,13 +387,43 @@ fn main() -> Result<()> {
 
     eprintln!("Found {} usage records.", records.len());
 
-    let pricing = pricing::load_pricing(&pricing_source, cli.offline)?;
+    let pricing = pricing::load_pricing(&pricing_source, cli.offline)?.with_basis(cost_basis);
 
-    let unpriced = pricing.unpriced_models(&records);
+    let unpriced = match cost_basis {
+        cost::CostBasis::Reported => Vec::new(),
+        _ => pricing.unpriced_models(&records),
+    };
     if !unpriced.is_empty() {
         eprintln!("No pricing data for: {}", unpriced.join(", "));
     }
 
+    if let cli::Command::Errors { ref by } = mode {
+        let report = errors::compute(&records, &pricing, by);
+        match cli.format {
+            cli::OutputFormat::Json => output::print_errors_json(&report, by, &exchange),
+            cli::OutputFormat::Table => output::print_errors_table(&report, by, &exchange),
+        }
+        return Ok(());
+    }
+
+    if let cli::Command::Compactions = mode {
+        let report = compaction::compute(&records, &pricing);
+        match cli.format {
+            cli::OutputFormat::Json => output::print_compactions_json(&report, &exchange),
+            cli::OutputFormat::Table => output::print_compactions_table(&report, &exchange),
+        }
+        return Ok(());
+    }
+
+    if let cli::Command::Tools = mode {
+        let report = tools::compute(&records, &pricing);
+        match cli.format {
+            cli::OutputFormat::Json => output::print_tools_json(&report, &exchange),
+            cli::OutputFormat::Table => output::print_tools_table(&report, &exchange),
+        }
+        return Ok(());
+    }
+
     if let cli::Command::ModelBurn { idle_gap } = mode {
         let report = burn::compute(&records, &pricing, idle_gap);
         match cli.format {
@@ -370,3 +446,47 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_filter_is_exact_and_skips_unbranched_records() {
+        let record = |branch: Option<&str>| types::UsageRecord {
+            provider: Provider::Codex,
+            session_id: "s".to_string(),
+            timestamp: chrono::Utc::now(),
+            project: "p".to_string(),
+            model: "m".to_string(),
+            message_id: "m".to_string(),
+            request_id: String::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: branch.map(String::from),
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: None,
+        };
+        let main = record(Some("main"));
+        let unbranched = record(None);
+
+        assert!(matches_branch(&main, None));
+        assert!(matches_branch(&unbranched, None));
+        assert!(matches_branch(&main, Some("main")));
+        assert!(!matches_branch(&main, Some("mai")));
+        assert!(!matches_branch(&record(Some("feature/main")), Some("main")));
+        assert!(!matches_branch(&unbranched, Some("main")));
+    }
+}
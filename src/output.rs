@@ -4,7 +4,11 @@ use comfy_table::{presets::UTF8_FULL_CONDENSED, Cell, ContentArrangement, Table}
 
 use crate::aggregate::short_model_name;
 use crate::burn;
+use crate::cli;
+use crate::compaction;
+use crate::errors;
 use crate::exchange::ExchangeRate;
+use crate::tools;
 use crate::types::AggregatedBucket;
 
 fn format_tokens(n: u64) -> String {
@@ -22,27 +26,58 @@ fn column_header(col: &str) -> &str {
         "period" => "Period",
         "input" => "Input",
         "output" => "Output",
+        "reasoning" => "Reasoning",
         "cache_write" => "Cache Write",
         "cache_read" => "Cache Read",
+        "cache_write_5m" => "Cache Write 5m",
+        "cache_write_1h" => "Cache Write 1h",
         "cost" => "Cost",
+        "discrepancy" => "Discrepancy",
+        "web_searches" => "Web Searches",
         "models" => "Models",
         "tools" => "Tools",
         "projects" => "Projects",
+        "tiers" => "Tiers",
+        "agents" => "Agents",
+        "branches" => "Branches",
         other => other,
     }
 }
 
+/// Signed reported-minus-computed cost; "–" when no record had both.
+fn discrepancy_cell(discrepancy: Option<f64>, exchange: &ExchangeRate) -> Cell {
+    match discrepancy {
+        Some(d) => {
+            let sign = if d < 0.0 { "-" } else { "+" };
+            Cell::new(format!("{sign}{}", exchange.format_cost(Some(d.abs()))))
+        }
+        None => Cell::new("–"),
+    }
+}
+
 fn bucket_cell(col: &str, key: &str, bucket: &AggregatedBucket, exchange: &ExchangeRate) -> Cell {
     match col {
         "period" => Cell::new(key),
         "input" => Cell::new(format_tokens(bucket.input_tokens)),
         "output" => Cell::new(format_tokens(bucket.output_tokens)),
+        "reasoning" => Cell::new(format_tokens(bucket.reasoning_tokens)),
         "cache_write" => Cell::new(format_tokens(bucket.cache_creation_input_tokens)),
         "cache_read" => Cell::new(format_tokens(bucket.cache_read_input_tokens)),
+        "cache_write_5m" => Cell::new(format_tokens(
+            bucket
+                .cache_creation_input_tokens
+                .saturating_sub(bucket.cache_creation_1h_input_tokens),
+        )),
+        "cache_write_1h" => Cell::new(format_tokens(bucket.cache_creation_1h_input_tokens)),
         "cost" => Cell::new(exchange.format_cost(bucket.cost)),
+        "discrepancy" => discrepancy_cell(bucket.cost_discrepancy, exchange),
+        "web_searches" => Cell::new(bucket.web_search_requests),
         "models" => Cell::new(bucket.models.join(", ")),
         "tools" => Cell::new(bucket.tools.join(", ")),
         "projects" => Cell::new(bucket.projects.join(", ")),
+        "tiers" => Cell::new(bucket.tiers.join(", ")),
+        "agents" => Cell::new(bucket.agents.join(", ")),
+        "branches" => Cell::new(bucket.branches.join(", ")),
         _ => Cell::new(""),
     }
 }
@@ -56,9 +91,18 @@ fn detail_cell(
         "period" => Cell::new(format!("  {}", detail.model)),
         "input" => Cell::new(format_tokens(detail.input_tokens)),
         "output" => Cell::new(format_tokens(detail.output_tokens)),
+        "reasoning" => Cell::new(format_tokens(detail.reasoning_tokens)),
         "cache_write" => Cell::new(format_tokens(detail.cache_creation_input_tokens)),
         "cache_read" => Cell::new(format_tokens(detail.cache_read_input_tokens)),
+        "cache_write_5m" => Cell::new(format_tokens(
+            detail
+                .cache_creation_input_tokens
+                .saturating_sub(detail.cache_creation_1h_input_tokens),
+        )),
+        "cache_write_1h" => Cell::new(format_tokens(detail.cache_creation_1h_input_tokens)),
         "cost" => Cell::new(exchange.format_cost(detail.cost)),
+        "discrepancy" => discrepancy_cell(detail.cost_discrepancy, exchange),
+        "web_searches" => Cell::new(detail.web_search_requests),
         _ => Cell::new(""),
     }
 }
@@ -186,6 +230,216 @@ pub fn print_burn_json(report: &burn::BurnReport, exchange: &ExchangeRate) {
     println!("{}", serde_json::to_string_pretty(&arr).unwrap_or_default());
 }
 
+fn error_row_cells(row: &errors::ErrorRow, key: &str, exchange: &ExchangeRate) -> Vec<Cell> {
+    vec![
+        Cell::new(key),
+        Cell::new(row.api_errors),
+        Cell::new(row.overloaded),
+        Cell::new(row.rate_limited),
+        Cell::new(row.interrupted),
+        Cell::new(format_tokens(row.lost_tokens)),
+        Cell::new(exchange.format_cost(row.lost_cost)),
+    ]
+}
+
+pub fn print_errors_table(
+    report: &errors::ErrorReport,
+    by: &cli::ErrorGrouping,
+    exchange: &ExchangeRate,
+) {
+    if report.rows.is_empty() {
+        eprintln!("No API errors or interrupted turns found.");
+        return;
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+
+    table.set_header(
+        [
+            by.column(),
+            "API Errors",
+            "Overloaded",
+            "Rate Limited",
+            "Interrupted",
+            "Lost Tokens",
+            "Lost Cost",
+        ]
+        .into_iter()
+        .map(Cell::new),
+    );
+
+    for row in &report.rows {
+        table.add_row(error_row_cells(row, &row.key, exchange));
+    }
+
+    table.add_row(error_row_cells(&report.total, "TOTAL", exchange));
+
+    println!("{table}");
+}
+
+pub fn print_errors_json(
+    report: &errors::ErrorReport,
+    by: &cli::ErrorGrouping,
+    exchange: &ExchangeRate,
+) {
+    let entry = |row: &errors::ErrorRow| {
+        let mut entry = serde_json::json!({
+            "api_errors": row.api_errors,
+            "overloaded": row.overloaded,
+            "rate_limited": row.rate_limited,
+            "interrupted": row.interrupted,
+            "lost_tokens": row.lost_tokens,
+            "lost_cost": row.lost_cost.map(|c| exchange.convert(c)),
+            "currency": exchange.code,
+        });
+        entry[by.json_key()] = serde_json::json!(row.key);
+        entry
+    };
+
+    let mut arr: Vec<serde_json::Value> = report.rows.iter().map(entry).collect();
+    arr.push(entry(&report.total));
+
+    println!("{}", serde_json::to_string_pretty(&arr).unwrap_or_default());
+}
+
+fn compaction_row_cells(row: &compaction::CompactionRow, exchange: &ExchangeRate) -> Vec<Cell> {
+    vec![
+        Cell::new(&row.session),
+        Cell::new(row.compactions),
+        Cell::new(row.manual),
+        Cell::new(format_tokens(row.summarized_tokens)),
+        Cell::new(row.avg_context().map_or("–".to_string(), format_tokens)),
+        Cell::new(exchange.format_cost(row.summary_cost)),
+        Cell::new(exchange.format_cost(row.session_cost)),
+    ]
+}
+
+pub fn print_compactions_table(report: &compaction::CompactionReport, exchange: &ExchangeRate) {
+    if report.rows.is_empty() {
+        eprintln!("No compactions found.");
+        return;
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+
+    table.set_header(
+        [
+            "Session",
+            "Compactions",
+            "Manual",
+            "Summarized",
+            "Avg Context",
+            "Summary Cost",
+            "Session Cost",
+        ]
+        .into_iter()
+        .map(Cell::new),
+    );
+
+    for row in &report.rows {
+        table.add_row(compaction_row_cells(row, exchange));
+    }
+
+    table.add_row(compaction_row_cells(&report.total, exchange));
+
+    println!("{table}");
+}
+
+pub fn print_compactions_json(report: &compaction::CompactionReport, exchange: &ExchangeRate) {
+    let entry = |row: &compaction::CompactionRow| {
+        serde_json::json!({
+            "session": row.session,
+            "compactions": row.compactions,
+            "manual": row.manual,
+            "summarized_tokens": row.summarized_tokens,
+            "avg_context_tokens": row.avg_context(),
+            "summary_cost": row.summary_cost.map(|c| exchange.convert(c)),
+            "session_cost": row.session_cost.map(|c| exchange.convert(c)),
+            "currency": exchange.code,
+        })
+    };
+
+    let mut arr: Vec<serde_json::Value> = report.rows.iter().map(entry).collect();
+    arr.push(entry(&report.total));
+
+    println!("{}", serde_json::to_string_pretty(&arr).unwrap_or_default());
+}
+
+fn tool_row_cells(row: &tools::ToolRow, label: &str, exchange: &ExchangeRate) -> Vec<Cell> {
+    vec![
+        Cell::new(label),
+        Cell::new(row.calls),
+        Cell::new(row.responses),
+        Cell::new(exchange.format_cost(row.cost)),
+    ]
+}
+
+pub fn print_tools_table(report: &tools::ToolReport, exchange: &ExchangeRate) {
+    if report.rows.is_empty() {
+        eprintln!("No tool calls found.");
+        return;
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+
+    table.set_header(
+        ["Tool", "Calls", "Responses", "Cost"]
+            .into_iter()
+            .map(Cell::new),
+    );
+
+    for row in &report.rows {
+        if row.is_mcp_server() {
+            table.add_row(tool_row_cells(
+                row,
+                &format!("{} (MCP)", row.name),
+                exchange,
+            ));
+            for tool in &row.tools {
+                table.add_row(tool_row_cells(
+                    tool,
+                    &format!("  └─ {}", tool.name),
+                    exchange,
+                ));
+            }
+        } else {
+            table.add_row(tool_row_cells(row, &row.name, exchange));
+        }
+    }
+
+    table.add_row(tool_row_cells(&report.total, "TOTAL", exchange));
+
+    println!("{table}");
+}
+
+pub fn print_tools_json(report: &tools::ToolReport, exchange: &ExchangeRate) {
+    fn entry(row: &tools::ToolRow, exchange: &ExchangeRate) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "tool": row.name,
+            "calls": row.calls,
+            "responses": row.responses,
+            "cost": row.cost.map(|c| exchange.convert(c)),
+            "currency": exchange.code,
+        });
+        if row.is_mcp_server() {
+            value["mcp_server"] = serde_json::json!(true);
+            value["tools"] = row.tools.iter().map(|t| entry(t, exchange)).collect();
+        }
+        value
+    }
+
+    let mut arr: Vec<serde_json::Value> = report.rows.iter().map(|r| entry(r, exchange)).collect();
+    arr.push(entry(&report.total, exchange));
+
+    println!("{}", serde_json::to_string_pretty(&arr).unwrap_or_default());
+}
+
 /// Waybar CSS class for the bar widget. Stringified for JSON consumers
 /// (waybar keys CSS rules on these literal names; do not rename).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -282,9 +536,14 @@ pub fn print_json(buckets: &BTreeMap<String, AggregatedBucket>, exchange: &Excha
                         "model": d.model,
                         "input_tokens": d.input_tokens,
                         "output_tokens": d.output_tokens,
+                        "reasoning_tokens": d.reasoning_tokens,
                         "cache_creation_input_tokens": d.cache_creation_input_tokens,
                         "cache_read_input_tokens": d.cache_read_input_tokens,
+                        "cache_creation_1h_input_tokens": d.cache_creation_1h_input_tokens,
+                        "web_search_requests": d.web_search_requests,
+                        "web_fetch_requests": d.web_fetch_requests,
                         "cost": d.cost.map(|c| exchange.convert(c)),
+                        "cost_discrepancy": d.cost_discrepancy.map(|c| exchange.convert(c)),
                     })
                 })
                 .collect();
@@ -295,11 +554,19 @@ pub fn print_json(buckets: &BTreeMap<String, AggregatedBucket>, exchange: &Excha
                     "currency": exchange.code,
                     "input_tokens": bucket.input_tokens,
                     "output_tokens": bucket.output_tokens,
+                    "reasoning_tokens": bucket.reasoning_tokens,
                     "cache_creation_input_tokens": bucket.cache_creation_input_tokens,
                     "cache_read_input_tokens": bucket.cache_read_input_tokens,
+                    "cache_creation_1h_input_tokens": bucket.cache_creation_1h_input_tokens,
+                    "web_search_requests": bucket.web_search_requests,
+                    "web_fetch_requests": bucket.web_fetch_requests,
                     "cost": bucket.cost.map(|c| exchange.convert(c)),
+                    "cost_discrepancy": bucket.cost_discrepancy.map(|c| exchange.convert(c)),
                     "models": bucket.models,
                     "projects": bucket.projects,
+                    "tiers": bucket.tiers,
+                    "agents": bucket.agents,
+                    "branches": bucket.branches,
                     "details": details,
                 }),
             )
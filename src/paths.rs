@@ -47,8 +47,27 @@ pub fn data_dir() -> Option<PathBuf> {
     }
 }
 
+/// Imported usage exports (`tku import-usage`). Kept outside the cache dir
+/// so that clearing the cache doesn't drop imported usage.
+pub fn imports_dir() -> Option<PathBuf> {
+    data_dir().map(|d| d.join("imports"))
+}
+
+/// Gemini `projectHash` → working directory map, built up as hashes are
+/// resolved. Kept outside the cache dir: directories deleted since can't be
+/// re-hashed, so the map is the only record of them.
+pub fn project_hashes_file() -> Option<PathBuf> {
+    data_dir().map(|d| d.join("project-hashes.json"))
+}
+
 // --- Cache files ---
 
+/// Gemini project hashes the code-roots walk couldn't match, with the state
+/// of the roots it walked.
+pub fn project_hash_misses_file() -> Option<PathBuf> {
+    cache_dir().map(|d| d.join("project-hash-misses.json"))
+}
+
 /// Pricing JSON cache, one per source: `pricing-litellm.json`, etc.
 pub fn pricing_cache_file(source: &str) -> Option<PathBuf> {
     cache_dir().map(|d| d.join(format!("pricing-{source}.json")))
@@ -82,6 +101,12 @@ pub fn profile_cache_file(org_uuid: &str) -> Option<PathBuf> {
     cache_dir().map(|d| d.join(format!("profile-{org_uuid}.json")))
 }
 
+/// Resume state (cursor and cached segments) per provider plugin:
+/// `plugin-<name>.json`. Lives next to the record cache it describes.
+pub fn plugin_state_file(name: &str) -> Option<PathBuf> {
+    cache_dir().map(|d| d.join(format!("plugin-{name}.json")))
+}
+
 // --- Config files ---
 
 pub fn config_file() -> Option<PathBuf> {
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use anyhow::Result;
 
-use crate::cost::ModelPricing;
+use crate::cost::{ModelPricing, TierPricing};
 
 const LITELLM_URL: &str =
     "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
@@ -38,12 +38,27 @@ pub fn parse_litellm_json(data: &str) -> Result<HashMap<String, ModelPricing>> {
         let cache_creation = val
             .get("cache_creation_input_token_cost")
             .and_then(|v| v.as_f64());
+        let cache_creation_1h = val
+            .get("cache_creation_input_token_cost_above_1hr")
+            .and_then(|v| v.as_f64());
+        let reasoning = val
+            .get("output_cost_per_reasoning_token")
+            .and_then(|v| v.as_f64());
+        let web_search = val
+            .get("search_context_cost_per_query")
+            .and_then(|v| v.get("search_context_size_medium"))
+            .and_then(|v| v.as_f64());
 
         let pricing = ModelPricing {
             input_cost_per_token: input,
             output_cost_per_token: output,
             cache_read_input_token_cost: cache_read,
             cache_creation_input_token_cost: cache_creation,
+            cache_creation_input_token_cost_above_1hr: cache_creation_1h,
+            output_cost_per_reasoning_token: reasoning,
+            web_search_cost_per_request: web_search,
+            web_fetch_cost_per_request: None,
+            tiers: parse_tiers(val),
         };
 
         // Store under the original key
@@ -58,6 +73,30 @@ pub fn parse_litellm_json(data: &str) -> Result<HashMap<String, ModelPricing>> {
     Ok(map)
 }
 
+/// Tier-specific prices, keyed in LiteLLM by a suffix on the standard
+/// field names (`input_cost_per_token_priority`, `..._batches`).
+fn parse_tiers(val: &serde_json::Value) -> Vec<TierPricing> {
+    [
+        ("priority", "priority"),
+        ("flex", "flex"),
+        ("batch", "batches"),
+    ]
+    .into_iter()
+    .filter_map(|(tier, suffix)| {
+        let rate = |field: &str| {
+            val.get(format!("{field}_{suffix}"))
+                .and_then(|v| v.as_f64())
+        };
+        Some(TierPricing {
+            tier,
+            input_cost_per_token: rate("input_cost_per_token")?,
+            output_cost_per_token: rate("output_cost_per_token")?,
+            cache_read_input_token_cost: rate("cache_read_input_token_cost"),
+        })
+    })
+    .collect()
+}
+
 /// Generate normalized variants of a LiteLLM key so Claude Code model names
 /// (e.g. "claude-opus-4-5-20251101") can be looked up directly.
 fn normalize_key(key: &str) -> Vec<String> {
@@ -53,6 +53,11 @@ pub fn parse_llmprices_json(data: &str) -> Result<HashMap<String, ModelPricing>>
             output_cost_per_token: output_per_m / 1_000_000.0,
             cache_read_input_token_cost: cache_read,
             cache_creation_input_token_cost: None,
+            cache_creation_input_token_cost_above_1hr: None,
+            output_cost_per_reasoning_token: None,
+            web_search_cost_per_request: None,
+            web_fetch_cost_per_request: None,
+            tiers: Vec::new(),
         };
 
         map.insert(id.to_string(), mp);
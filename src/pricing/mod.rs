@@ -12,7 +12,7 @@ use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
 use crate::atomic_write::atomic_write;
-use crate::cost::{ModelPricing, PricingMap};
+use crate::cost::{CostBasis, ModelPricing, PricingMap};
 use crate::paths;
 
 const CACHE_TTL_SECS: u64 = 24 * 60 * 60;
@@ -38,12 +38,51 @@ impl std::fmt::Display for PricingSource {
 
 pub struct CachedPricing {
     map: HashMap<String, ModelPricing>,
+    basis: CostBasis,
+    priority_multiplier: Option<f64>,
+}
+
+impl CachedPricing {
+    /// Wrap a parsed price list, applying per-request price overrides from
+    /// the `[pricing]` config table to every model.
+    fn new(mut map: HashMap<String, ModelPricing>) -> Self {
+        let overrides = crate::config::load_config().pricing;
+        if overrides.web_search_cost_per_request.is_some()
+            || overrides.web_fetch_cost_per_request.is_some()
+        {
+            for p in map.values_mut() {
+                if let Some(c) = overrides.web_search_cost_per_request {
+                    p.web_search_cost_per_request = Some(c);
+                }
+                if let Some(c) = overrides.web_fetch_cost_per_request {
+                    p.web_fetch_cost_per_request = Some(c);
+                }
+            }
+        }
+        Self {
+            map,
+            basis: CostBasis::default(),
+            priority_multiplier: overrides.priority_multiplier,
+        }
+    }
+
+    pub fn with_basis(self, basis: CostBasis) -> Self {
+        Self { basis, ..self }
+    }
 }
 
 impl PricingMap for CachedPricing {
     fn get(&self, model: &str) -> Option<&ModelPricing> {
         self.map.get(model)
     }
+
+    fn basis(&self) -> CostBasis {
+        self.basis
+    }
+
+    fn priority_multiplier(&self) -> Option<f64> {
+        self.priority_multiplier
+    }
 }
 
 fn cache_path(source: &PricingSource) -> Option<PathBuf> {
@@ -87,7 +126,7 @@ pub fn load_pricing(source: &PricingSource, offline: bool) -> Result<CachedPrici
         if offline || cache_is_fresh(path) {
             if let Ok(data) = fs::read_to_string(path) {
                 if let Ok(map) = parse_raw(source, &data) {
-                    return Ok(CachedPricing { map });
+                    return Ok(CachedPricing::new(map));
                 }
             }
             if offline {
@@ -114,5 +153,5 @@ pub fn load_pricing(source: &PricingSource, offline: bool) -> Result<CachedPrici
         }
     }
 
-    Ok(CachedPricing { map })
+    Ok(CachedPricing::new(map))
 }
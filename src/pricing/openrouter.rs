@@ -55,11 +55,27 @@ pub fn parse_openrouter_json(data: &str) -> Result<HashMap<String, ModelPricing>
             .and_then(|v| v.as_str())
             .and_then(|s| s.parse::<f64>().ok());
 
+        let reasoning = pricing
+            .and_then(|p| p.get("internal_reasoning"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|c| *c > 0.0);
+        let web_search = pricing
+            .and_then(|p| p.get("web_search"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|c| *c > 0.0);
+
         let mp = ModelPricing {
             input_cost_per_token: input,
             output_cost_per_token: output,
             cache_read_input_token_cost: cache_read,
             cache_creation_input_token_cost: cache_creation,
+            cache_creation_input_token_cost_above_1hr: None,
+            output_cost_per_reasoning_token: reasoning,
+            web_search_cost_per_request: web_search,
+            web_fetch_cost_per_request: None,
+            tiers: Vec::new(),
         };
 
         // Store under full ID (e.g. "anthropic/claude-opus-4-5")
@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::atomic_write::atomic_write;
+use crate::paths;
+use crate::types::{Provider, UsageRecord};
+
+/// How a resolved project is named in reports (`[projects] name`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectNaming {
+    /// The repository's directory name
+    #[default]
+    Dir,
+    /// The `origin` remote as `owner/repo`, falling back to the directory
+    Remote,
+}
+
+/// A working directory resolved to the project it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectId {
+    /// Display name: the repository's directory name (see [`label`]).
+    pub name: String,
+    /// Unique key: the repository root, or the directory itself outside git.
+    pub path: String,
+}
+
+/// Resolve a working directory to its project.
+///
+/// Inside a git checkout the project is the repository: subdirectories of a
+/// monorepo collapse onto the toplevel, and linked worktrees onto the main
+/// checkout. Directories that no longer exist or aren't in a repository
+/// stand for themselves. Only local files are read (`.git`, `commondir`);
+/// results are cached per directory for the life of the process.
+pub fn resolve(cwd: &str) -> ProjectId {
+    static CACHE: OnceLock<Mutex<HashMap<String, ProjectId>>> = OnceLock::new();
+    let cwd = cwd.trim_end_matches(['/', '\\']);
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(id) = cache.lock().ok().and_then(|c| c.get(cwd).cloned()) {
+        return id;
+    }
+
+    let id = resolve_uncached(Path::new(cwd));
+    if let Ok(mut c) = cache.lock() {
+        c.insert(cwd.to_string(), id.clone());
+    }
+    id
+}
+
+/// The `--project` filter, with `needle` already lowercased: a substring of
+/// the display name, or the repository path itself or a run of its trailing
+/// components (`acme/api`). Paths aren't substring-matched, so `api`
+/// doesn't pull in every repo under `~/src/api-work/`.
+pub fn matches(record: &UsageRecord, needle: &str) -> bool {
+    if record.project.to_lowercase().contains(needle) {
+        return true;
+    }
+    let needle = needle.trim_end_matches(['/', '\\']);
+    let path = record.project_path.to_lowercase();
+    let path = path.trim_end_matches(['/', '\\']);
+    !needle.is_empty()
+        && (path == needle
+            || path
+                .strip_suffix(needle)
+                .is_some_and(|rest| rest.ends_with(['/', '\\'])))
+}
+
+/// Final display names for a scan's records.
+///
+/// Runs at report time rather than scan time so `[projects] name` applies
+/// to cached records too. With `remote` naming, repositories are named by
+/// their `origin` slug. Projects that still share a name get distinct ones,
+/// so two repos called `api` don't merge: each becomes `<parent>/api`, or
+/// its full path if the parents collide too. Gemini sessions only know a
+/// hash of their directory; see [`resolve_hashes`].
+pub fn label(records: &mut [UsageRecord], naming: ProjectNaming) {
+    resolve_hashes(records);
+
+    if naming == ProjectNaming::Remote {
+        let mut slugs: HashMap<String, Option<String>> = HashMap::new();
+        for r in records.iter_mut().filter(|r| !r.project_path.is_empty()) {
+            let slug = slugs
+                .entry(r.project_path.clone())
+                .or_insert_with(|| remote_name(Path::new(&r.project_path)));
+            if let Some(slug) = slug {
+                r.project.clone_from(slug);
+            }
+        }
+    }
+
+    let mut paths_by_name: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for r in records.iter().filter(|r| !r.project_path.is_empty()) {
+        paths_by_name
+            .entry(r.project.as_str())
+            .or_default()
+            .insert(r.project_path.as_str());
+    }
+
+    let mut renames: HashMap<(String, String), String> = HashMap::new();
+    for (name, paths) in paths_by_name.into_iter().filter(|(_, p)| p.len() > 1) {
+        let qualified = |path: &str| {
+            let parent = Path::new(path).parent().map(last_segment);
+            format!("{}/{name}", parent.as_deref().unwrap_or("unknown"))
+        };
+        let labels: HashSet<String> = paths.iter().map(|p| qualified(p)).collect();
+        for path in paths.iter() {
+            let label = if labels.len() == paths.len() {
+                qualified(path)
+            } else {
+                path.to_string()
+            };
+            renames.insert((name.to_string(), path.to_string()), label);
+        }
+    }
+    if renames.is_empty() {
+        return;
+    }
+
+    for r in records.iter_mut() {
+        if let Some(label) = renames.get(&(r.project.clone(), r.project_path.clone())) {
+            r.project = label.clone();
+        }
+    }
+}
+
+/// Gemini CLI (and Qwen Code) name a session's project by the SHA-256 of
+/// its working directory. Turn those back into projects by hashing known
+/// directories: the persisted map first, then the repository roots other
+/// tools' records point at (sessions started at a repo's top level), then
+/// every directory under the code roots. Matches are saved, so a directory
+/// that's since been deleted stays resolvable; so are hashes the walk
+/// didn't match, so it only reruns once the code roots change.
+fn resolve_hashes(records: &mut [UsageRecord]) {
+    let is_hashed = |r: &UsageRecord| {
+        matches!(r.provider, Provider::Gemini | Provider::Qwen)
+            && r.project_path.is_empty()
+            && is_project_hash(&r.project)
+    };
+    let hashes: HashSet<String> = records
+        .iter()
+        .filter(|r| is_hashed(r))
+        .map(|r| r.project.clone())
+        .collect();
+    if hashes.is_empty() {
+        return;
+    }
+
+    let mut known = load_project_hashes();
+    let mut missing: HashSet<String> = hashes
+        .into_iter()
+        .filter(|h| !known.contains_key(h))
+        .collect();
+
+    if !missing.is_empty() {
+        let seen: Vec<PathBuf> = records
+            .iter()
+            .filter(|r| !r.project_path.is_empty())
+            .map(|r| PathBuf::from(&r.project_path))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let found_before = known.len();
+        match_hashes(&mut missing, &mut known, seen);
+
+        let roots = crate::providers::code_roots();
+        let mut misses = load_hash_misses();
+        let state = roots_state(&roots);
+        if misses.roots != state {
+            misses = HashMisses {
+                roots: state,
+                hashes: BTreeSet::new(),
+            };
+        }
+        missing.retain(|h| !misses.hashes.contains(h));
+        if !missing.is_empty() {
+            match_hashes(
+                &mut missing,
+                &mut known,
+                crate::providers::dirs_in_code_roots(&roots),
+            );
+            misses.hashes.extend(missing);
+            save_hash_misses(&misses);
+        }
+        if known.len() > found_before {
+            save_project_hashes(&known);
+        }
+    }
+
+    for r in records.iter_mut().filter(|r| is_hashed(r)) {
+        if let Some(cwd) = known.get(&r.project) {
+            let id = resolve(cwd);
+            r.project = id.name;
+            r.project_path = id.path;
+        }
+    }
+}
+
+/// Hashes the code-roots walk didn't match, and the roots it walked.
+#[derive(Default, Serialize, Deserialize)]
+struct HashMisses {
+    roots: Vec<(String, i64)>,
+    hashes: BTreeSet<String>,
+}
+
+/// Each code root with its mtime, which moves when a directory is added to
+/// or removed from it.
+fn roots_state(roots: &[PathBuf]) -> Vec<(String, i64)> {
+    roots
+        .iter()
+        .map(|root| {
+            let mtime = std::fs::metadata(root)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs() as i64);
+            (root.to_string_lossy().into_owned(), mtime)
+        })
+        .collect()
+}
+
+fn load_hash_misses() -> HashMisses {
+    paths::project_hash_misses_file()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_hash_misses(misses: &HashMisses) {
+    let Some(path) = paths::project_hash_misses_file() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_vec(misses) {
+        let _ = atomic_write(&path, &data, None);
+    }
+}
+
+fn match_hashes(
+    missing: &mut HashSet<String>,
+    known: &mut BTreeMap<String, String>,
+    candidates: Vec<PathBuf>,
+) {
+    for dir in candidates {
+        if missing.is_empty() {
+            return;
+        }
+        let dir = dir.to_string_lossy();
+        let hash = project_hash(&dir);
+        if missing.remove(&hash) {
+            known.insert(hash, dir.into_owned());
+        }
+    }
+}
+
+/// Gemini CLI's `projectHash`: hex SHA-256 of the directory path as written.
+fn project_hash(dir: &str) -> String {
+    let digest = Sha256::digest(dir.trim_end_matches(['/', '\\']).as_bytes());
+    format!("{digest:x}")
+}
+
+fn is_project_hash(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn load_project_hashes() -> BTreeMap<String, String> {
+    paths::project_hashes_file()
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_project_hashes(known: &BTreeMap<String, String>) {
+    let Some(path) = paths::project_hashes_file() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_vec_pretty(known) {
+        if let Err(e) = atomic_write(&path, &data, None) {
+            eprintln!("tku: failed to save {}: {e}", path.display());
+        }
+    }
+}
+
+fn resolve_uncached(cwd: &Path) -> ProjectId {
+    let Some((root, git_dir)) = find_repository(cwd) else {
+        return ProjectId {
+            name: last_segment(cwd),
+            path: cwd.to_string_lossy().to_string(),
+        };
+    };
+
+    let common_dir = common_git_dir(&git_dir);
+    // A linked worktree's common dir is the main checkout's `.git`.
+    let root = match common_dir.file_name() {
+        Some(n) if n == ".git" && common_dir != git_dir => {
+            common_dir.parent().map(Path::to_path_buf).unwrap_or(root)
+        }
+        _ => root,
+    };
+
+    ProjectId {
+        name: last_segment(&root),
+        path: root.to_string_lossy().to_string(),
+    }
+}
+
+/// The `origin` slug of the repository rooted at `root`, read from its
+/// `.git/config`.
+fn remote_name(root: &Path) -> Option<String> {
+    let (_, git_dir) = find_repository(root)?;
+    origin_slug(&common_git_dir(&git_dir).join("config"))
+}
+
+fn last_segment(path: &Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// The nearest ancestor holding `.git`, with the git directory it points at
+/// (`.git` is a file containing `gitdir: <path>` in worktrees and submodules).
+fn find_repository(cwd: &Path) -> Option<(PathBuf, PathBuf)> {
+    cwd.ancestors().find_map(|dir| {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some((dir.to_path_buf(), dot_git));
+        }
+        let content = std::fs::read_to_string(&dot_git).ok()?;
+        let target = content.strip_prefix("gitdir:")?.trim();
+        Some((dir.to_path_buf(), dir.join(target)))
+    })
+}
+
+/// Worktrees keep shared state (config, refs) in the dir named by `commondir`.
+fn common_git_dir(git_dir: &Path) -> PathBuf {
+    match std::fs::read_to_string(git_dir.join("commondir")) {
+        Ok(rel) => normalize(&git_dir.join(rel.trim())),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+/// Fold `..` components so `<repo>/.git/worktrees/x/../..` reads as
+/// `<repo>/.git`.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            std::path::Component::CurDir => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `owner/repo` from the `origin` remote's URL in a git config file.
+fn origin_slug(config: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(config).ok()?;
+    let mut in_origin = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_origin = line == "[remote \"origin\"]";
+            continue;
+        }
+        if !in_origin {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim() == "url" {
+                return slug_from_url(value.trim());
+            }
+        }
+    }
+    None
+}
+
+/// `git@host:owner/repo.git`, `https://host/owner/repo` and friends.
+fn slug_from_url(url: &str) -> Option<String> {
+    let path = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?.1,
+        None => url.split_once(':')?.1,
+    };
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = path.rsplit('/');
+    let repo = parts.next().filter(|s| !s.is_empty())?;
+    let owner = parts.next().filter(|s| !s.is_empty())?;
+    Some(format!("{owner}/{repo}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_filter_matches_name_or_whole_path_components() {
+        let r = UsageRecord {
+            provider: Provider::Claude,
+            session_id: "s".to_string(),
+            timestamp: chrono::Utc::now(),
+            project: "acme/API".to_string(),
+            model: "m".to_string(),
+            message_id: "m".to_string(),
+            request_id: String::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: "/home/me/src/api-work/acme-api".to_string(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: None,
+        };
+        assert!(matches(&r, "api"), "name substring");
+        assert!(matches(&r, "acme-api"));
+        assert!(matches(&r, "api-work/acme-api/"));
+        assert!(matches(&r, "/home/me/src/api-work/acme-api"));
+        assert!(!matches(&r, "work"), "no substring match on the path");
+        assert!(!matches(&r, "me-api"), "suffix must start at a component");
+        assert!(!matches(&r, "/home/me/src"));
+    }
+
+    #[test]
+    fn remote_urls_reduce_to_owner_and_repo() {
+        assert_eq!(
+            slug_from_url("git@github.com:acme/api.git").as_deref(),
+            Some("acme/api")
+        );
+        assert_eq!(
+            slug_from_url("https://gitlab.com/group/sub/api").as_deref(),
+            Some("sub/api")
+        );
+        assert_eq!(slug_from_url("/srv/git/api.git"), None);
+    }
+
+    #[test]
+    fn project_hash_matches_gemini_cli() {
+        let hash = "dbb3fbfaca6b9e811de341326f12ce38007deea084967b6ff1b9a13f8bcd790e";
+        assert_eq!(project_hash("/home/me/src/api"), hash);
+        assert_eq!(project_hash("/home/me/src/api/"), hash);
+        assert!(is_project_hash(hash));
+        assert!(!is_project_hash("gemini"));
+    }
+
+    #[test]
+    fn subdirectories_and_worktrees_resolve_to_the_main_checkout() {
+        let tmp = std::env::temp_dir().join(format!("tku-project-{}", std::process::id()));
+        let repo = tmp.join("api");
+        let worktree = tmp.join("api-feature");
+        std::fs::create_dir_all(repo.join(".git/worktrees/api-feature")).unwrap();
+        std::fs::create_dir_all(repo.join("packages/web")).unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(
+            repo.join(".git/config"),
+            "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = git@github.com:acme/api.git\n",
+        )
+        .unwrap();
+        std::fs::write(repo.join(".git/worktrees/api-feature/commondir"), "../..\n").unwrap();
+        std::fs::write(
+            worktree.join(".git"),
+            format!(
+                "gitdir: {}\n",
+                repo.join(".git/worktrees/api-feature").display()
+            ),
+        )
+        .unwrap();
+
+        let expected = ProjectId {
+            name: "api".to_string(),
+            path: repo.to_string_lossy().to_string(),
+        };
+        assert_eq!(resolve_uncached(&repo.join("packages/web")), expected);
+        assert_eq!(resolve_uncached(&worktree), expected);
+        assert_eq!(remote_name(&repo).as_deref(), Some("acme/api"));
+
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
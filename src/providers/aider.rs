@@ -0,0 +1,513 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+use super::{
+    code_roots, discover_and_parse_with, discover_in_code_roots, discovered_file,
+    parse_jsonl_lines, DiscoveredFile, Provider as ProviderDriver,
+};
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+const CHAT_HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Aider writes its chat history into each repository rather than a central
+/// directory, so discovery walks the configured code roots. Analytics logs
+/// (`--analytics-log`) are opt-in and listed explicitly in the config.
+pub struct AiderProvider;
+
+impl ProviderDriver for AiderProvider {
+    fn id(&self) -> Provider {
+        Provider::Aider
+    }
+
+    /// Aider's files are scattered across repositories; watch the files
+    /// themselves instead of recursively watching whole code roots.
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        let (histories, logs) = discover();
+        histories.into_iter().chain(logs).map(|f| f.path).collect()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let (histories, logs) = discover();
+        let starts = if logs.is_empty() {
+            Vec::new()
+        } else {
+            chat_starts(&histories)
+        };
+
+        // A chat that shows up in an analytics log is counted from the log
+        // only: it carries exact token counts and per-message timestamps,
+        // while the chat history rounds counts ("12k sent") and only
+        // timestamps the start of each chat. Chats from before the log was
+        // turned on are still read from the history.
+        let covered: HashSet<(i64, &Path)> = logs
+            .iter()
+            .flat_map(|log| launch_times(&log.path))
+            .filter_map(|time| chat_for_launch(&starts, time))
+            .map(|(ts, repo)| (*ts, repo.as_path()))
+            .collect();
+        // Which chats are covered changes with the logs, so histories are
+        // keyed on them too.
+        let mut files: Vec<DiscoveredFile> = histories
+            .into_iter()
+            .map(|f| logs.iter().fold(f, |f, log| f.depending_on(&log.path)))
+            .collect();
+        files.extend(logs);
+
+        discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
+            if path.file_name().is_some_and(|n| n == CHAT_HISTORY_FILE) {
+                parse_chat_history(path, &covered)
+            } else {
+                parse_analytics_log(path, &starts)
+            }
+        });
+    }
+}
+
+fn analytics_logs() -> Vec<PathBuf> {
+    crate::config::load_config()
+        .providers
+        .aider
+        .analytics_logs
+        .iter()
+        .map(|p| crate::config::expand_home(p))
+        .collect()
+}
+
+/// Chat histories from the code roots, and the configured analytics logs.
+fn discover() -> (Vec<DiscoveredFile>, Vec<DiscoveredFile>) {
+    let logs = analytics_logs()
+        .iter()
+        .filter_map(|p| discovered_file(p))
+        .collect();
+    let histories = discover_in_code_roots(&code_roots(), |p| {
+        p.file_name().is_some_and(|n| n == CHAT_HISTORY_FILE)
+    });
+    (histories, logs)
+}
+
+/// How far apart a chat history's `# aider chat started at` header and the
+/// analytics `launched` event of the same aider process may be.
+const LAUNCH_MATCH_SECS: i64 = 30;
+
+/// Start time of every chat in the histories, with the repository it ran in,
+/// sorted by time.
+fn chat_starts(histories: &[DiscoveredFile]) -> Vec<(i64, PathBuf)> {
+    let mut starts: Vec<(i64, PathBuf)> = histories
+        .iter()
+        .filter_map(|f| Some((f, f.path.parent()?)))
+        .flat_map(|(f, repo)| {
+            let file = std::fs::File::open(&f.path).ok();
+            file.into_iter()
+                .flat_map(|file| BufReader::new(file).lines().map_while(Result::ok))
+                .filter_map(|line| {
+                    let rest = line.strip_prefix("# aider chat started at ")?;
+                    parse_local_timestamp(rest.trim()).map(|ts| ts.timestamp())
+                })
+                .map(move |ts| (ts, repo.to_path_buf()))
+        })
+        .collect();
+    starts.sort();
+    starts
+}
+
+fn launch_times(log: &Path) -> Vec<i64> {
+    parse_jsonl_lines(log, "\"launched\"", |line: &str| {
+        let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
+        (parsed.get("event")?.as_str()? == "launched")
+            .then(|| parsed.get("time")?.as_i64())
+            .flatten()
+    })
+}
+
+/// The chat an aider process launched at `time` opened, as its start and
+/// repository: analytics events don't record a directory, but the process
+/// also started a chat in that repository's history within seconds of
+/// launching.
+fn chat_for_launch(starts: &[(i64, PathBuf)], time: i64) -> Option<&(i64, PathBuf)> {
+    starts
+        .iter()
+        .filter(|(ts, _)| (ts - time).abs() <= LAUNCH_MATCH_SECS)
+        .min_by_key(|(ts, _)| (ts - time).abs())
+}
+
+/// Project: the directory holding the file, as long as it looks like a
+/// repository. Aider's files sit at the git root.
+fn project_from_path(path: &Path) -> ProjectId {
+    path.parent()
+        .filter(|dir| {
+            path.file_name().is_some_and(|n| n == CHAT_HISTORY_FILE) || dir.join(".git").exists()
+        })
+        .and_then(|dir| dir.to_str())
+        .filter(|dir| !dir.is_empty())
+        .map(crate::project::resolve)
+        .unwrap_or_else(|| ProjectId {
+            name: "aider".to_string(),
+            path: String::new(),
+        })
+}
+
+fn file_mtime(path: &Path) -> Option<DateTime<Utc>> {
+    std::fs::metadata(path)
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .and_then(|d| DateTime::<Utc>::from_timestamp(d.as_secs() as i64, 0))
+}
+
+/// Parse a `.aider.chat.history.md` transcript.
+///
+/// Aider logs one `> Tokens: …` line per LLM call. Timestamps only appear in
+/// the `# aider chat started at …` header, so every call in a chat is dated
+/// to the chat's start. Model switches (`/model`) re-print the model banner,
+/// which we track as we go. Chats in `covered` (start, repository) are
+/// counted from an analytics log and skipped.
+fn parse_chat_history(path: &Path, covered: &HashSet<(i64, &Path)>) -> Vec<UsageRecord> {
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Vec::new(),
+    };
+
+    let project = project_from_path(path);
+    let fallback_ts = file_mtime(path).unwrap_or_else(Utc::now);
+
+    let reader = BufReader::new(file);
+    let mut records = Vec::new();
+    let mut started: Option<DateTime<Utc>> = None;
+    let mut model: Option<String> = None;
+    let mut skipping = false;
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(l) => l,
+            Err(_) => continue,
+        };
+
+        if let Some(rest) = line.strip_prefix("# aider chat started at ") {
+            started = parse_local_timestamp(rest.trim());
+            model = None;
+            skipping = match (started, path.parent()) {
+                (Some(ts), Some(repo)) => covered.contains(&(ts.timestamp(), repo)),
+                _ => false,
+            };
+            continue;
+        }
+        if skipping {
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("> ") else {
+            continue;
+        };
+
+        if let Some(name) = rest
+            .strip_prefix("Main model: ")
+            .or_else(|| rest.strip_prefix("Model: "))
+        {
+            model = name
+                .split(" with ")
+                .next()
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty());
+            continue;
+        }
+
+        let Some(tokens) = parse_tokens_line(rest) else {
+            continue;
+        };
+        if tokens.sent == 0 && tokens.received == 0 {
+            continue;
+        }
+
+        let timestamp = started.unwrap_or(fallback_ts);
+        let session_id = timestamp.format("%Y-%m-%dT%H:%M:%S").to_string();
+        // "sent" is the full prompt, cached classes included.
+        let input = tokens
+            .sent
+            .saturating_sub(tokens.cache_write + tokens.cache_hit);
+
+        records.push(UsageRecord {
+            provider: Provider::Aider,
+            session_id: session_id.clone(),
+            timestamp,
+            project: project.name.clone(),
+            model: model.clone().unwrap_or_else(|| "unknown".to_string()),
+            message_id: format!("aider:{}:{session_id}:{line_no}", project.name),
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: tokens.received,
+            cache_creation_input_tokens: tokens.cache_write,
+            cache_read_input_tokens: tokens.cache_hit,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: project.path.clone(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: tokens.cost,
+        });
+    }
+
+    records
+}
+
+/// Aider writes the chat header in local time without an offset.
+fn parse_local_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok()?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TokenLine {
+    sent: u64,
+    cache_write: u64,
+    cache_hit: u64,
+    received: u64,
+    /// The `$X message` part of the cost segment, when aider knows the price.
+    cost: Option<f64>,
+}
+
+/// Parse `Tokens: 2.6k sent, 1.2k cache write, 5.1k cache hit, 340 received. Cost: …`.
+/// The cache segments only appear for providers that report them.
+fn parse_tokens_line(line: &str) -> Option<TokenLine> {
+    let body = line.strip_prefix("Tokens: ")?;
+    let mut segments = body.split(". ");
+    let counts = segments.next()?.trim_end_matches('.');
+
+    let mut tokens = TokenLine::default();
+    for part in counts.split(", ") {
+        let (num, label) = part.trim().split_once(' ')?;
+        let n = parse_abbreviated(num)?;
+        match label {
+            "sent" => tokens.sent = n,
+            "received" => tokens.received = n,
+            "cache write" => tokens.cache_write = n,
+            "cache hit" => tokens.cache_hit = n,
+            _ => {}
+        }
+    }
+    tokens.cost = segments
+        .find_map(|s| s.strip_prefix("Cost: "))
+        .and_then(|s| s.split(", ").find_map(|p| p.strip_suffix(" message")))
+        .and_then(|p| p.trim_start_matches('$').parse::<f64>().ok())
+        .filter(|c| *c > 0.0);
+    Some(tokens)
+}
+
+/// Undo aider's `format_tokens`: `340`, `2.6k`, `12k`, `1.5M`.
+fn parse_abbreviated(s: &str) -> Option<u64> {
+    let (digits, scale) = match s.as_bytes().last()? {
+        b'k' | b'K' => (&s[..s.len() - 1], 1_000.0),
+        b'M' | b'm' => (&s[..s.len() - 1], 1_000_000.0),
+        _ => (s, 1.0),
+    };
+    let value: f64 = digits.parse().ok()?;
+    (value >= 0.0).then(|| (value * scale).round() as u64)
+}
+
+/// Parse an analytics log (`--analytics-log`): one JSON event per line.
+///
+/// `launched` events mark a new aider process and serve as session
+/// boundaries; each `message_send` event carries exact token counts. A
+/// session's project is the repository whose chat history it opened (see
+/// [`chat_for_launch`]), falling back to the log's own repository.
+fn parse_analytics_log(path: &Path, starts: &[(i64, PathBuf)]) -> Vec<UsageRecord> {
+    let log_project = project_from_path(path);
+    let session_start: Cell<Option<i64>> = Cell::new(None);
+    let project = RefCell::new(log_project.clone());
+
+    parse_jsonl_lines(path, "\"event\"", |line: &str| {
+        let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
+        let time = parsed.get("time").and_then(|v| v.as_i64())?;
+
+        match parsed.get("event").and_then(|v| v.as_str())? {
+            "launched" => {
+                session_start.set(Some(time));
+                *project.borrow_mut() = chat_for_launch(starts, time)
+                    .and_then(|(_, repo)| repo.to_str())
+                    .map(crate::project::resolve)
+                    .unwrap_or_else(|| log_project.clone());
+                None
+            }
+            "message_send" => {
+                let props = parsed.get("properties")?;
+                let input = props
+                    .get("prompt_tokens")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                let output = props
+                    .get("completion_tokens")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                if input == 0 && output == 0 {
+                    return None;
+                }
+                let model = props
+                    .get("main_model")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                let timestamp = DateTime::<Utc>::from_timestamp(time, 0)?;
+                let session = session_start.get().unwrap_or(time);
+                let cost = props
+                    .get("cost")
+                    .and_then(|v| v.as_f64())
+                    .filter(|c| *c > 0.0);
+                let project = project.borrow();
+
+                Some(UsageRecord {
+                    provider: Provider::Aider,
+                    session_id: session.to_string(),
+                    timestamp,
+                    project: project.name.clone(),
+                    model,
+                    message_id: format!("aider:{time}:{input}:{output}"),
+                    request_id: String::new(),
+                    input_tokens: input,
+                    output_tokens: output,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 0,
+                    cache_creation_1h_input_tokens: 0,
+                    reasoning_tokens: 0,
+                    web_search_requests: 0,
+                    web_fetch_requests: 0,
+                    service_tier: None,
+                    agent: None,
+                    git_branch: None,
+                    project_path: project.path.clone(),
+                    event: None,
+                    failed_turn: false,
+                    tool_calls: Vec::new(),
+                    account_uuid: None,
+                    reported_cost: cost,
+                })
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abbreviated_counts_round_trip_aider_format() {
+        assert_eq!(parse_abbreviated("340"), Some(340));
+        assert_eq!(parse_abbreviated("2.6k"), Some(2_600));
+        assert_eq!(parse_abbreviated("12k"), Some(12_000));
+        assert_eq!(parse_abbreviated("1.5M"), Some(1_500_000));
+        assert_eq!(parse_abbreviated("k"), None);
+    }
+
+    #[test]
+    fn tokens_line_with_and_without_cache_segments() {
+        assert_eq!(
+            parse_tokens_line(
+                "Tokens: 12k sent, 340 received. Cost: $0.04 message, $0.10 session."
+            ),
+            Some(TokenLine {
+                sent: 12_000,
+                received: 340,
+                cost: Some(0.04),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parse_tokens_line("Tokens: 9.8k sent, 1.2k cache write, 5.1k cache hit, 88 received."),
+            Some(TokenLine {
+                sent: 9_800,
+                cache_write: 1_200,
+                cache_hit: 5_100,
+                received: 88,
+                cost: None,
+            })
+        );
+        assert_eq!(parse_tokens_line("Tokens: lots sent"), None);
+    }
+
+    #[test]
+    fn central_log_sessions_take_the_repo_they_launched_in() {
+        let dir = std::env::temp_dir().join(format!("tku-aider-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("analytics.jsonl");
+        std::fs::write(
+            &log,
+            concat!(
+                "{\"event\":\"launched\",\"time\":1000}\n",
+                "{\"event\":\"message_send\",\"time\":1010,\"properties\":{\"main_model\":\"m\",\"prompt_tokens\":10,\"completion_tokens\":2}}\n",
+                "{\"event\":\"launched\",\"time\":5000}\n",
+                "{\"event\":\"message_send\",\"time\":5010,\"properties\":{\"main_model\":\"m\",\"prompt_tokens\":30,\"completion_tokens\":4}}\n",
+            ),
+        )
+        .unwrap();
+        let starts = vec![
+            (990, PathBuf::from("/nonexistent/tku/other")),
+            (1004, PathBuf::from("/nonexistent/tku/api")),
+        ];
+
+        assert_eq!(launch_times(&log), vec![1000, 5000]);
+        let records = parse_analytics_log(&log, &starts);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].project, "api");
+        assert_eq!(records[0].session_id, "1000");
+        assert_eq!(records[1].project, "aider", "no chat started near 5000");
+    }
+
+    #[test]
+    fn only_chats_seen_in_an_analytics_log_are_skipped() {
+        let repo = std::env::temp_dir().join(format!("tku-aider-history-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        let history = repo.join(CHAT_HISTORY_FILE);
+        std::fs::write(
+            &history,
+            concat!(
+                "# aider chat started at 2025-03-01 10:00:00\n",
+                "> Main model: gpt-4o with diff edit format\n",
+                "> Tokens: 1.2k sent, 100 received.\n",
+                "# aider chat started at 2025-03-02 10:00:00\n",
+                "> Tokens: 9k sent, 300 received.\n",
+            ),
+        )
+        .unwrap();
+
+        let starts = chat_starts(&[discovered_file(&history).unwrap()]);
+        assert_eq!(starts.len(), 2);
+        let launched = starts[1].0 - 5;
+        let covered: HashSet<(i64, &Path)> = chat_for_launch(&starts, launched)
+            .map(|(ts, repo)| (*ts, repo.as_path()))
+            .into_iter()
+            .collect();
+        let records = parse_chat_history(&history, &covered);
+        std::fs::remove_dir_all(&repo).unwrap();
+
+        assert_eq!(records.len(), 1, "the earlier chat predates the log");
+        assert_eq!(
+            (records[0].input_tokens, records[0].output_tokens),
+            (1_200, 100)
+        );
+        assert_eq!(records[0].model, "gpt-4o");
+    }
+}
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use chrono::{DateTime, Utc};
@@ -7,7 +9,7 @@ use super::{
     HomeFallback, Provider as ProviderDriver, XdgBase,
 };
 use crate::storage::Storage;
-use crate::types::{Provider, UsageRecord};
+use crate::types::{normalize_service_tier, Provider, UsageRecord};
 
 pub struct ClaudeProvider;
 
@@ -75,28 +77,301 @@ fn compute_roots() -> Vec<PathBuf> {
 }
 
 fn parse_jsonl_file(path: &std::path::Path) -> Vec<UsageRecord> {
-    let session_id = path
-        .file_stem()
-        .and_then(|s| s.to_str())
+    // Subagent transcripts live in `<session>/subagents/agent-<id>.jsonl`;
+    // their usage belongs to the parent session.
+    let parent_session = path
+        .parent()
+        .filter(|dir| dir.file_name().is_some_and(|n| n == "subagents"))
+        .and_then(|dir| dir.parent())
+        .and_then(|dir| dir.file_name())
+        .and_then(|n| n.to_str());
+    let session_id = parent_session
+        .or_else(|| path.file_stem().and_then(|s| s.to_str()))
         .unwrap_or("unknown")
         .to_string();
 
     let project = extract_project_from_path(path);
+    // Task tool_use id → subagent type, for labelling the agent_progress
+    // lines that report the subagent's usage back into the main transcript.
+    let task_agents: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    let turns: RefCell<Turns> = RefCell::new(Turns::default());
+    // Message id → (tool_use id, tool name) for every call in the message.
+    let calls: RefCell<HashMap<String, Vec<(String, String)>>> = RefCell::new(HashMap::new());
 
-    parse_jsonl_lines(path, "\"type\":", |line: &str| {
-        // Pre-filter: skip lines that can't contain usage data
-        if !line.contains("\"type\":\"assistant\"") && !line.contains("\"type\":\"progress\"") {
+    let mut records = parse_jsonl_lines(path, "\"type\":", |line: &str| {
+        // Pre-filter: skip lines that can't contain usage data, end a turn
+        // or mark a compaction. Usage lines go first: progress lines embed
+        // the subagent's own user messages, tool results included. Of the
+        // remaining user lines, only tool results don't start a new turn.
+        let carries_usage = line.contains("\"type\":\"assistant\"")
+            || line.contains("\"type\":\"progress\"")
+            || line.contains("\"compact_boundary\"");
+        if !carries_usage
+            && (!line.contains("\"type\":\"user\"")
+                || line.contains("\"tool_result\"") && !line.contains(INTERRUPTED_MARKER))
+        {
             return None;
         }
 
         let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
         let line_type = parsed.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let mut turns = turns.borrow_mut();
 
-        match line_type {
-            "assistant" | "progress" => extract_record(&parsed, &session_id, &project),
-            _ => None,
+        let mut record = match line_type {
+            "user" => {
+                if parsed.get("isMeta").and_then(|v| v.as_bool()) == Some(true) {
+                    return None;
+                }
+                // The summary a compaction left behind, not a new prompt.
+                if parsed.get("isCompactSummary").and_then(|v| v.as_bool()) == Some(true) {
+                    if let Some(index) = turns.last_compaction.take() {
+                        turns.summaries.push((index, estimate_tokens(&parsed)));
+                    }
+                    return None;
+                }
+                if !is_interruption(&parsed) {
+                    turns.start_next();
+                    return None;
+                }
+                let model = turns.model.clone();
+                let record = event_record(&parsed, &session_id, &project, model, "interrupted");
+                turns.fail_current();
+                turns.start_next();
+                record?
+            }
+            "assistant" => {
+                collect_task_agents(&parsed, &mut task_agents.borrow_mut());
+                if let Some(kind) = api_error_kind(&parsed) {
+                    let model = turns.model.clone();
+                    turns.fail_current();
+                    event_record(&parsed, &session_id, &project, model, kind)?
+                } else {
+                    extract_record(&parsed, &session_id, &project)?
+                }
+            }
+            "progress" => extract_record(&parsed, &session_id, &project)?,
+            "system" => {
+                if parsed.get("subtype").and_then(|v| v.as_str()) != Some("compact_boundary") {
+                    return None;
+                }
+                let metadata = parsed.get("compactMetadata");
+                let event = match metadata
+                    .and_then(|m| m.get("trigger"))
+                    .and_then(|v| v.as_str())
+                {
+                    Some("manual") => "manual_compact",
+                    _ => "auto_compact",
+                };
+                let model = turns.model.clone();
+                let mut record = event_record(&parsed, &session_id, &project, model, event)?;
+                // The summarization call re-reads the whole context, which is
+                // cached by then; its output is the summary that follows.
+                record.cache_read_input_tokens = metadata
+                    .and_then(|m| m.get("preTokens"))
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                turns.last_compaction = Some(turns.record_turns.len());
+                record
+            }
+            _ => return None,
+        };
+        if line_type == "assistant" && record.event.is_none() {
+            turns.model = Some(record.model.clone());
         }
-    })
+        record.agent = agent_for(&parsed, parent_session.is_some(), &task_agents.borrow());
+        if let Some(message) = message_of(&parsed).filter(|_| !record.message_id.is_empty()) {
+            let mut calls = calls.borrow_mut();
+            let known = calls.entry(record.message_id.clone()).or_default();
+            for (id, name) in tool_uses(message) {
+                if !known.iter().any(|(k, _)| k == id) {
+                    known.push((id.to_string(), name.to_string()));
+                }
+            }
+        }
+        turns.note_record();
+        Some(record)
+    });
+
+    // Flag the usage of failed turns and size the summaries now that the
+    // whole transcript is read.
+    let turns = turns.into_inner();
+    for (record, turn) in records.iter_mut().zip(&turns.record_turns) {
+        record.failed_turn = record.event.is_none() && turns.failed.contains(turn);
+    }
+    for (index, tokens) in turns.summaries {
+        if let Some(record) = records.get_mut(index) {
+            record.output_tokens = tokens;
+        }
+    }
+    // A message's content blocks are logged one line each under the same
+    // id, so give every copy the full list; dedup keeps only one of them.
+    let calls = calls.into_inner();
+    for record in &mut records {
+        if let Some(message_calls) = calls.get(&record.message_id) {
+            record.tool_calls = message_calls.iter().map(|(_, name)| name.clone()).collect();
+        }
+    }
+    records
+}
+
+/// What Claude Code writes into the transcript when the user hits Esc,
+/// optionally followed by ` for tool use]`.
+const INTERRUPTED_MARKER: &str = "[Request interrupted by user";
+
+fn is_interruption(parsed: &serde_json::Value) -> bool {
+    let Some(content) = parsed.get("message").and_then(|m| m.get("content")) else {
+        return false;
+    };
+    let starts_with_marker = |text: &str| text.starts_with(INTERRUPTED_MARKER);
+    match content {
+        serde_json::Value::String(text) => starts_with_marker(text),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .any(starts_with_marker),
+        _ => false,
+    }
+}
+
+/// Turn bookkeeping while reading a transcript. A turn runs from one user
+/// prompt to the next, including any subagent usage reported in between.
+#[derive(Default)]
+struct Turns {
+    current: usize,
+    /// Model of the main thread's latest response in this transcript,
+    /// charged with the events that follow it.
+    model: Option<String>,
+    failed: HashSet<usize>,
+    /// Turn of each record returned so far, in order.
+    record_turns: Vec<usize>,
+    /// Index of the compaction marker still waiting for its summary.
+    last_compaction: Option<usize>,
+    /// Marker index and estimated summary size, applied after parsing.
+    summaries: Vec<(usize, u64)>,
+}
+
+impl Turns {
+    fn start_next(&mut self) {
+        self.current += 1;
+    }
+
+    fn fail_current(&mut self) {
+        self.failed.insert(self.current);
+    }
+
+    fn note_record(&mut self) {
+        self.record_turns.push(self.current);
+    }
+}
+
+/// Classify the synthetic assistant message Claude Code writes in place of a
+/// response when the API call failed for good (after its own retries).
+/// None for real responses and for non-error synthetic messages.
+fn api_error_kind(parsed: &serde_json::Value) -> Option<&'static str> {
+    let message = parsed.get("message")?;
+    let text = message
+        .get("content")
+        .and_then(|c| c.as_array())
+        .and_then(|blocks| {
+            blocks
+                .iter()
+                .find_map(|b| b.get("text").and_then(|t| t.as_str()))
+        })
+        .unwrap_or("");
+    let flagged = parsed.get("isApiErrorMessage").and_then(|v| v.as_bool()) == Some(true);
+    let synthetic = message.get("model").and_then(|v| v.as_str()) == Some("<synthetic>");
+    let is_error = flagged || (synthetic && text.starts_with("API Error"));
+    if !is_error {
+        return None;
+    }
+
+    let error = parsed.get("error").and_then(|v| v.as_str()).unwrap_or("");
+    let text = text.to_ascii_lowercase();
+    if text.contains("overloaded") || text.contains("529") {
+        Some("overloaded")
+    } else if error == "rate_limit"
+        || text.contains("rate_limit")
+        || text.contains("rate limit")
+        || text.contains("429")
+        || text.contains("limit reached")
+    {
+        Some("rate_limited")
+    } else {
+        Some("api_error")
+    }
+}
+
+/// The API message a usage line reports on: the line's own for `assistant`,
+/// the subagent's for `progress`.
+fn message_of(parsed: &serde_json::Value) -> Option<&serde_json::Value> {
+    match parsed.get("type")?.as_str()? {
+        "assistant" => parsed.get("message"),
+        "progress" => parsed.get("data")?.get("message")?.get("message"),
+        _ => None,
+    }
+}
+
+/// `(id, name)` of each tool_use block in a message.
+fn tool_uses(message: &serde_json::Value) -> impl Iterator<Item = (&str, &str)> {
+    message
+        .get("content")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|block| block.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+        .filter_map(|block| {
+            let id = block.get("id").and_then(|v| v.as_str())?;
+            let name = block.get("name").and_then(|v| v.as_str())?;
+            Some((id, name))
+        })
+}
+
+/// Remember the subagent type of every Task (`Agent` in newer versions)
+/// tool call in an assistant message.
+fn collect_task_agents(parsed: &serde_json::Value, task_agents: &mut HashMap<String, String>) {
+    let Some(content) = parsed
+        .get("message")
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+    else {
+        return;
+    };
+    for block in content {
+        let is_task = block.get("type").and_then(|v| v.as_str()) == Some("tool_use")
+            && matches!(
+                block.get("name").and_then(|v| v.as_str()),
+                Some("Task" | "Agent")
+            );
+        if !is_task {
+            continue;
+        }
+        let id = block.get("id").and_then(|v| v.as_str());
+        let agent_type = block
+            .get("input")
+            .and_then(|i| i.get("subagent_type"))
+            .and_then(|v| v.as_str());
+        if let (Some(id), Some(agent_type)) = (id, agent_type) {
+            task_agents.insert(id.to_string(), agent_type.to_string());
+        }
+    }
+}
+
+/// Which thread a usage line came from: None for the main conversation,
+/// otherwise the subagent type, or `subagent` when it can't be resolved.
+fn agent_for(
+    parsed: &serde_json::Value,
+    in_subagent_file: bool,
+    task_agents: &HashMap<String, String>,
+) -> Option<String> {
+    if parsed.get("type").and_then(|v| v.as_str()) == Some("progress") {
+        let agent_type = parsed
+            .get("parentToolUseID")
+            .and_then(|v| v.as_str())
+            .and_then(|id| task_agents.get(id));
+        return Some(agent_type.map_or("subagent", String::as_str).to_string());
+    }
+    let sidechain = parsed.get("isSidechain").and_then(|v| v.as_bool()) == Some(true);
+    (in_subagent_file || sidechain).then(|| "subagent".to_string())
 }
 
 fn extract_project_from_path(path: &std::path::Path) -> String {
@@ -190,11 +465,22 @@ fn extract_record(
     let message_id = message.get("id").and_then(|v| v.as_str()).unwrap_or("");
     let request_id = request_id_val.and_then(|v| v.as_str()).unwrap_or("");
 
-    let project = parsed
-        .get("cwd")
-        .and_then(|v| v.as_str())
-        .map(|cwd| cwd.rsplit('/').next().unwrap_or(project).to_string())
-        .unwrap_or_else(|| project.to_string());
+    // Newer transcripts split cache writes by TTL; the flat count is the sum.
+    let cache_creation_1h = usage
+        .get("cache_creation")
+        .and_then(|c| c.get("ephemeral_1h_input_tokens"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    let server_tool_use = |key: &str| {
+        usage
+            .get("server_tool_use")
+            .and_then(|s| s.get(key))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0)
+    };
+
+    let (project, project_path) = project_of(parsed, project);
 
     Some(UsageRecord {
         provider: Provider::Claude,
@@ -220,7 +506,290 @@ fn extract_record(
             .get("cache_read_input_tokens")
             .and_then(|v| v.as_u64())
             .unwrap_or(0),
+        cache_creation_1h_input_tokens: cache_creation_1h,
+        reasoning_tokens: 0,
+        web_search_requests: server_tool_use("web_search_requests"),
+        web_fetch_requests: server_tool_use("web_fetch_requests"),
+        service_tier: usage
+            .get("service_tier")
+            .and_then(|v| v.as_str())
+            .map(normalize_service_tier),
+        agent: None,
+        git_branch: git_branch(parsed),
+        project_path,
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         // Filled in by discover_and_parse via per-record account_at lookup.
         account_uuid: None,
+        reported_cost: None,
     })
 }
+
+/// A token-less record marking an event in the session (see
+/// `UsageRecord::event`). `model` is the one the session was last running
+/// on, when any response got in.
+fn event_record(
+    parsed: &serde_json::Value,
+    session_id: &str,
+    project: &str,
+    model: Option<String>,
+    event: &str,
+) -> Option<UsageRecord> {
+    let timestamp: DateTime<Utc> = parsed.get("timestamp")?.as_str()?.parse().ok()?;
+    let (project, project_path) = project_of(parsed, project);
+    let uuid = parsed.get("uuid").and_then(|v| v.as_str()).unwrap_or("");
+
+    Some(UsageRecord {
+        provider: Provider::Claude,
+        session_id: session_id.to_string(),
+        timestamp,
+        project,
+        model: model.unwrap_or_else(|| "<synthetic>".to_string()),
+        message_id: format!("{event}:{uuid}"),
+        request_id: String::new(),
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: 0,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: 0,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: git_branch(parsed),
+        project_path,
+        event: Some(event.to_string()),
+        failed_turn: false,
+        tool_calls: Vec::new(),
+        account_uuid: None,
+        reported_cost: None,
+    })
+}
+
+/// Rough token count of a message's text, at ~4 characters per token.
+fn estimate_tokens(parsed: &serde_json::Value) -> u64 {
+    let chars = match parsed.get("message").and_then(|m| m.get("content")) {
+        Some(serde_json::Value::String(text)) => text.chars().count(),
+        Some(serde_json::Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .map(|t| t.chars().count())
+            .sum(),
+        _ => 0,
+    };
+    chars.div_ceil(4) as u64
+}
+
+/// Project of a transcript line: its `cwd`, else the projects-folder name.
+fn project_of(parsed: &serde_json::Value, fallback: &str) -> (String, String) {
+    match parsed.get("cwd").and_then(|v| v.as_str()) {
+        Some(cwd) if !cwd.is_empty() => {
+            let id = crate::project::resolve(cwd);
+            (id.name, id.path)
+        }
+        _ => (fallback.to_string(), String::new()),
+    }
+}
+
+fn git_branch(parsed: &serde_json::Value) -> Option<String> {
+    parsed
+        .get("gitBranch")
+        .and_then(|v| v.as_str())
+        .filter(|b| !b.is_empty())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_lines_take_the_agent_type_of_their_task_call() {
+        let task: serde_json::Value = serde_json::from_str(
+            r#"{"type":"assistant","message":{"content":[
+                {"type":"tool_use","id":"toolu_1","name":"Task","input":{"subagent_type":"Explore"}}
+            ]}}"#,
+        )
+        .unwrap();
+        let mut task_agents = HashMap::new();
+        collect_task_agents(&task, &mut task_agents);
+
+        let progress =
+            |parent: &str| serde_json::json!({"type": "progress", "parentToolUseID": parent});
+        assert_eq!(
+            agent_for(&progress("toolu_1"), false, &task_agents).as_deref(),
+            Some("Explore")
+        );
+        assert_eq!(
+            agent_for(&progress("toolu_2"), false, &task_agents).as_deref(),
+            Some("subagent")
+        );
+        assert_eq!(agent_for(&task, false, &task_agents), None);
+        assert_eq!(
+            agent_for(&task, true, &task_agents).as_deref(),
+            Some("subagent")
+        );
+    }
+
+    #[test]
+    fn progress_lines_embedding_tool_results_keep_their_usage() {
+        let path = std::env::temp_dir().join(format!("tku-progress-{}.jsonl", std::process::id()));
+        let progress = serde_json::json!({
+            "type": "progress",
+            "parentToolUseID": "toolu_1",
+            "timestamp": "2026-10-14T10:00:05Z",
+            "data": {
+                "type": "agent_progress",
+                "prompt": [{"type": "user", "message": {"content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_9", "content": "ok"}
+                ]}}],
+                "message": {
+                    "type": "assistant",
+                    "timestamp": "2026-10-14T10:00:05Z",
+                    "requestId": "req_1",
+                    "message": {"id": "m1", "model": "claude-sonnet-4-5",
+                                "usage": {"input_tokens": 10, "output_tokens": 5}}
+                }
+            }
+        });
+        let tool_result = serde_json::json!({
+            "type": "user",
+            "timestamp": "2026-10-14T10:00:06Z",
+            "message": {"content": [{"type": "tool_result", "tool_use_id": "toolu_9"}]}
+        });
+        std::fs::write(&path, format!("{progress}\n{tool_result}")).unwrap();
+        let records = parse_jsonl_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].message_id, "m1");
+        assert_eq!(records[0].agent.as_deref(), Some("subagent"));
+    }
+
+    #[test]
+    fn compactions_carry_the_summarized_context_and_summary() {
+        let path = std::env::temp_dir().join(format!("tku-compact-{}.jsonl", std::process::id()));
+        let lines = [
+            r#"{"type":"assistant","timestamp":"2026-10-14T10:00:05Z","message":{"id":"m1","model":"claude-sonnet-4-5","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+            r#"{"type":"system","subtype":"compact_boundary","uuid":"b1","timestamp":"2026-10-14T10:05:00Z","compactMetadata":{"trigger":"manual","preTokens":160000}}"#,
+            r#"{"type":"user","isCompactSummary":true,"timestamp":"2026-10-14T10:05:01Z","message":{"content":"0123456789"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let records = parse_jsonl_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 2);
+        let compaction = &records[1];
+        assert_eq!(compaction.event.as_deref(), Some("manual_compact"));
+        assert_eq!(compaction.model, "claude-sonnet-4-5");
+        assert_eq!(compaction.cache_read_input_tokens, 160000);
+        assert_eq!(compaction.output_tokens, 3);
+    }
+
+    /// Input 1, output 2, cache reads 0.5 per token.
+    struct CompactionPricing(crate::cost::ModelPricing);
+    impl crate::cost::PricingMap for CompactionPricing {
+        fn get(&self, _model: &str) -> Option<&crate::cost::ModelPricing> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn compaction_report_counts_per_session() {
+        let dir = std::env::temp_dir().join(format!("tku-compact-report-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let compacted = dir.join("compacted.jsonl");
+        std::fs::write(
+            &compacted,
+            [
+                r#"{"type":"assistant","timestamp":"2026-10-14T10:00:05Z","message":{"id":"m1","model":"claude-sonnet-4-5","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+                r#"{"type":"system","subtype":"compact_boundary","uuid":"b1","timestamp":"2026-10-14T10:05:00Z","compactMetadata":{"trigger":"manual","preTokens":1000}}"#,
+                r#"{"type":"user","isCompactSummary":true,"timestamp":"2026-10-14T10:05:01Z","message":{"content":"0123456789"}}"#,
+                r#"{"type":"assistant","timestamp":"2026-10-14T10:06:00Z","message":{"id":"m2","model":"claude-sonnet-4-5","usage":{"input_tokens":4,"output_tokens":1}}}"#,
+                r#"{"type":"system","subtype":"compact_boundary","uuid":"b2","timestamp":"2026-10-14T11:00:00Z","compactMetadata":{"trigger":"auto","preTokens":2000}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let plain = dir.join("plain.jsonl");
+        std::fs::write(
+            &plain,
+            r#"{"type":"assistant","timestamp":"2026-10-14T12:00:00Z","message":{"id":"m3","model":"claude-sonnet-4-5","usage":{"input_tokens":7,"output_tokens":0}}}"#,
+        )
+        .unwrap();
+        let mut records = parse_jsonl_file(&compacted);
+        records.extend(parse_jsonl_file(&plain));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let pricing = CompactionPricing(crate::cost::ModelPricing {
+            input_cost_per_token: 1.0,
+            output_cost_per_token: 2.0,
+            cache_read_input_token_cost: Some(0.5),
+            cache_creation_input_token_cost: None,
+            cache_creation_input_token_cost_above_1hr: None,
+            output_cost_per_reasoning_token: None,
+            web_search_cost_per_request: None,
+            web_fetch_cost_per_request: None,
+            tiers: Vec::new(),
+        });
+        let report = crate::compaction::compute(&records, &pricing);
+
+        assert_eq!(
+            report.rows.len(),
+            1,
+            "sessions without compactions are left out"
+        );
+        let row = &report.rows[0];
+        assert_eq!(row.session, "unknown | compacted");
+        assert_eq!((row.compactions, row.manual), (2, 1));
+        assert_eq!(row.summarized_tokens, 3000);
+        assert_eq!(row.avg_context(), Some(1500));
+        // 1000 × 0.5 + 3 summary tokens × 2, then 2000 × 0.5.
+        assert_eq!(row.summary_cost, Some(1506.0));
+        // (10 + 5 × 2) + (4 + 1 × 2); the other session doesn't count.
+        assert_eq!(row.session_cost, Some(26.0));
+        assert_eq!(report.total.compactions, 2);
+        assert_eq!(report.total.session_cost, Some(26.0));
+    }
+
+    #[test]
+    fn synthetic_error_messages_are_classified() {
+        let error = |text: &str| {
+            serde_json::json!({
+                "type": "assistant",
+                "isApiErrorMessage": true,
+                "message": {"model": "<synthetic>", "content": [{"type": "text", "text": text}]},
+            })
+        };
+        assert_eq!(
+            api_error_kind(&error(
+                r#"API Error: 529 {"type":"error","error":{"type":"overloaded_error"}}"#
+            )),
+            Some("overloaded")
+        );
+        assert_eq!(
+            api_error_kind(&error("Claude AI usage limit reached|1760000000")),
+            Some("rate_limited")
+        );
+        assert_eq!(
+            api_error_kind(&error("API Error: Connection error.")),
+            Some("api_error")
+        );
+
+        let no_response = serde_json::json!({
+            "type": "assistant",
+            "message": {"model": "<synthetic>", "content": [{"type": "text", "text": "No response requested."}]},
+        });
+        assert_eq!(api_error_kind(&no_response), None);
+
+        let user = |content: serde_json::Value| serde_json::json!({"type": "user", "message": {"content": content}});
+        assert!(is_interruption(&user(serde_json::json!(
+            [{"type": "text", "text": "[Request interrupted by user for tool use]"}]
+        ))));
+        assert!(!is_interruption(&user(serde_json::json!(
+            "why did you print [Request interrupted by user]?"
+        ))));
+    }
+}
@@ -0,0 +1,397 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+
+use super::{
+    compute_provider_roots, discover_and_parse_with, discover_files_with, HomeFallback,
+    Provider as ProviderDriver, XdgBase,
+};
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Cline and its forks (Roo Code, Kilo Code) share one task-history layout
+/// under VS Code's `globalStorage/<extension-id>/tasks/<task-id>/`. Only the
+/// extension id differs, so a single driver is instantiated once per fork.
+pub struct ClineProvider {
+    id: Provider,
+    extension_id: &'static str,
+}
+
+impl ClineProvider {
+    pub const CLINE: Self = Self {
+        id: Provider::Cline,
+        extension_id: "saoudrizwan.claude-dev",
+    };
+    pub const ROO_CODE: Self = Self {
+        id: Provider::RooCode,
+        extension_id: "rooveterinaryinc.roo-cline",
+    };
+    pub const KILO_CODE: Self = Self {
+        id: Provider::KiloCode,
+        extension_id: "kilocode.kilo-code",
+    };
+
+    fn compute_roots(&self) -> Vec<PathBuf> {
+        compute_provider_roots(None, &[], EDITOR_STORAGE)
+            .into_iter()
+            .map(|storage| storage.join(self.extension_id).join("tasks"))
+            .collect()
+    }
+}
+
+/// `globalStorage` dirs of the editors that host VS Code extensions. Linux
+/// keeps them under XDG config, macOS under `~/Library/Application Support`;
+/// missing roots are skipped by discovery, so listing both is harmless.
+const EDITOR_STORAGE: &[HomeFallback] = &[
+    HomeFallback {
+        base: XdgBase::Config,
+        subpaths: &["Code", "User", "globalStorage"],
+    },
+    HomeFallback {
+        base: XdgBase::Config,
+        subpaths: &["Code - Insiders", "User", "globalStorage"],
+    },
+    HomeFallback {
+        base: XdgBase::Config,
+        subpaths: &["VSCodium", "User", "globalStorage"],
+    },
+    HomeFallback {
+        base: XdgBase::Config,
+        subpaths: &["Cursor", "User", "globalStorage"],
+    },
+    HomeFallback {
+        base: XdgBase::Home,
+        subpaths: &[
+            "Library",
+            "Application Support",
+            "Code",
+            "User",
+            "globalStorage",
+        ],
+    },
+    HomeFallback {
+        base: XdgBase::Home,
+        subpaths: &[
+            "Library",
+            "Application Support",
+            "Code - Insiders",
+            "User",
+            "globalStorage",
+        ],
+    },
+    HomeFallback {
+        base: XdgBase::Home,
+        subpaths: &[
+            "Library",
+            "Application Support",
+            "VSCodium",
+            "User",
+            "globalStorage",
+        ],
+    },
+    HomeFallback {
+        base: XdgBase::Home,
+        subpaths: &[
+            "Library",
+            "Application Support",
+            "Cursor",
+            "User",
+            "globalStorage",
+        ],
+    },
+];
+
+impl ProviderDriver for ClineProvider {
+    fn id(&self) -> Provider {
+        self.id
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        self.compute_roots()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let roots = self.compute_roots();
+        let files = discover_files_with(&roots, |p| {
+            p.file_name().is_some_and(|n| n == "ui_messages.json")
+        });
+        let id = self.id;
+        discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
+            parse_task(path, id)
+        });
+    }
+}
+
+#[derive(Deserialize)]
+struct UiMessage {
+    ts: Option<i64>,
+    #[serde(rename = "type")]
+    msg_type: Option<String>,
+    say: Option<String>,
+    text: Option<String>,
+}
+
+/// Payload of an `api_req_started` message, stored as a JSON string in `text`.
+#[derive(Deserialize)]
+struct ApiReqInfo {
+    request: Option<String>,
+    #[serde(rename = "tokensIn")]
+    tokens_in: Option<u64>,
+    #[serde(rename = "tokensOut")]
+    tokens_out: Option<u64>,
+    #[serde(rename = "cacheWrites")]
+    cache_writes: Option<u64>,
+    #[serde(rename = "cacheReads")]
+    cache_reads: Option<u64>,
+    /// What the extension computed or the upstream (e.g. OpenRouter) billed.
+    cost: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct TaskMetadata {
+    model_usage: Option<Vec<ModelUsageEntry>>,
+}
+
+#[derive(Deserialize)]
+struct ModelUsageEntry {
+    ts: Option<i64>,
+    model_id: Option<String>,
+}
+
+fn parse_task(path: &Path, id: Provider) -> Vec<UsageRecord> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    let messages: Vec<UiMessage> = match serde_json::from_str(&content) {
+        Ok(m) => m,
+        Err(_) => return Vec::new(),
+    };
+
+    let task_dir = path.parent();
+    let task_id = task_dir
+        .and_then(|d| d.file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let models = task_dir.map(read_model_usage).unwrap_or_default();
+    // Only dig through the (potentially large) conversation history when the
+    // task has no metadata to take the model from.
+    let fallback_model = if models.is_empty() {
+        task_dir.and_then(read_history_model)
+    } else {
+        None
+    }
+    .unwrap_or_else(|| "unknown".to_string());
+
+    let mut project: Option<ProjectId> = None;
+    let mut records = Vec::new();
+
+    for msg in &messages {
+        if msg.msg_type.as_deref() != Some("say") || msg.say.as_deref() != Some("api_req_started") {
+            continue;
+        }
+        let Some(ts) = msg.ts else { continue };
+        let Some(info) = msg
+            .text
+            .as_deref()
+            .and_then(|t| serde_json::from_str::<ApiReqInfo>(t).ok())
+        else {
+            continue;
+        };
+
+        // The first request of a task carries the environment details; the
+        // working directory doesn't change within a task, so keep the first hit.
+        if project.is_none() {
+            project = info
+                .request
+                .as_deref()
+                .and_then(cwd_from_request)
+                .map(crate::project::resolve);
+        }
+
+        let input = info.tokens_in.unwrap_or(0);
+        let output = info.tokens_out.unwrap_or(0);
+        if input == 0 && output == 0 {
+            continue;
+        }
+
+        let Some(timestamp) = Utc.timestamp_millis_opt(ts).single() else {
+            continue;
+        };
+
+        let model = model_at(&models, ts).unwrap_or(&fallback_model).to_string();
+
+        records.push(UsageRecord {
+            provider: id,
+            session_id: task_id.clone(),
+            timestamp,
+            project: String::new(), // filled in below once the task's cwd is known
+            model,
+            message_id: format!("{}:{task_id}:{ts}", id.as_str()),
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: info.cache_writes.unwrap_or(0),
+            cache_read_input_tokens: info.cache_reads.unwrap_or(0),
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: info.cost.filter(|c| *c > 0.0),
+        });
+    }
+
+    let project = project.unwrap_or_else(|| ProjectId {
+        name: id.as_str().to_string(),
+        path: String::new(),
+    });
+    for r in &mut records {
+        r.project = project.name.clone();
+        r.project_path = project.path.clone();
+    }
+
+    records
+}
+
+/// Read `task_metadata.json` (Cline 3.x+), which logs every model switch
+/// within a task as `{ts, model_id}`. Returned sorted by timestamp.
+fn read_model_usage(task_dir: &Path) -> Vec<(i64, String)> {
+    let Ok(content) = std::fs::read_to_string(task_dir.join("task_metadata.json")) else {
+        return Vec::new();
+    };
+    let Ok(meta) = serde_json::from_str::<TaskMetadata>(&content) else {
+        return Vec::new();
+    };
+    let mut usage: Vec<(i64, String)> = meta
+        .model_usage
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|e| Some((e.ts?, e.model_id?)))
+        .collect();
+    usage.sort_by_key(|(ts, _)| *ts);
+    usage
+}
+
+/// Model active at `ts`: the latest switch at or before it, else the first.
+fn model_at(models: &[(i64, String)], ts: i64) -> Option<&str> {
+    models
+        .iter()
+        .rev()
+        .find(|(switched, _)| *switched <= ts)
+        .or_else(|| models.first())
+        .map(|(_, m)| m.as_str())
+}
+
+/// Roo Code and Kilo Code don't write `task_metadata.json`, but embed the
+/// active model as `<model>…</model>` in the environment details of each
+/// user turn. Take the last one in the conversation history.
+fn read_history_model(task_dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(task_dir.join("api_conversation_history.json")).ok()?;
+    let start = content.rfind("<model>")? + "<model>".len();
+    let len = content[start..].find("</model>")?;
+    let model = content[start..start + len].trim();
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+/// Pull the working directory out of the environment details embedded in
+/// the request text: `# Current Working Directory (/home/me/git/foo) Files`.
+fn cwd_from_request(request: &str) -> Option<&str> {
+    let marker = "Current Working Directory (";
+    let start = request.find(marker)? + marker.len();
+    let len = request[start..].find(')')?;
+    Some(request[start..start + len].trim()).filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_at_takes_latest_switch_before_ts() {
+        let models = vec![(100, "a".to_string()), (200, "b".to_string())];
+        assert_eq!(model_at(&models, 50), Some("a"));
+        assert_eq!(model_at(&models, 150), Some("a"));
+        assert_eq!(model_at(&models, 200), Some("b"));
+        assert_eq!(model_at(&[], 200), None);
+    }
+
+    #[test]
+    fn cwd_from_environment_details() {
+        assert_eq!(
+            cwd_from_request(
+                "<environment_details>\n# Current Working Directory (/home/me/git/foo) Files\n"
+            ),
+            Some("/home/me/git/foo")
+        );
+        assert_eq!(
+            cwd_from_request("Current Working Directory ( ) Files"),
+            None
+        );
+        assert_eq!(cwd_from_request("no details"), None);
+    }
+
+    #[test]
+    fn task_records_take_model_switches_and_cwd() {
+        let dir = std::env::temp_dir()
+            .join(format!("tku-cline-{}", std::process::id()))
+            .join("1760000000000");
+        std::fs::create_dir_all(&dir).unwrap();
+        let req = |request: &str, tokens_in: u64, cost: f64| {
+            serde_json::json!({
+                "request": request, "tokensIn": tokens_in, "tokensOut": 20,
+                "cacheWrites": 5, "cacheReads": 7, "cost": cost,
+            })
+            .to_string()
+        };
+        let messages = serde_json::json!([
+            {"ts": 1000, "type": "say", "say": "text", "text": "hello"},
+            {"ts": 1001, "type": "say", "say": "api_req_started",
+             "text": req("# Current Working Directory (/nonexistent/tku/foo) Files", 100, 0.01)},
+            {"ts": 1002, "type": "say", "say": "api_req_started", "text": r#"{"tokensIn":0,"tokensOut":0}"#},
+            {"ts": 3000, "type": "say", "say": "api_req_started", "text": req("", 300, 0.0)},
+        ]);
+        std::fs::write(dir.join("ui_messages.json"), messages.to_string()).unwrap();
+        std::fs::write(
+            dir.join("task_metadata.json"),
+            r#"{"model_usage":[{"ts":2000,"model_id":"m2"},{"ts":900,"model_id":"m1"}]}"#,
+        )
+        .unwrap();
+
+        let records = parse_task(&dir.join("ui_messages.json"), Provider::RooCode);
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        assert_eq!(records.len(), 2, "zero-token requests are skipped");
+        assert_eq!(records[0].model, "m1");
+        assert_eq!(records[1].model, "m2");
+        assert_eq!(records[0].session_id, "1760000000000");
+        assert_eq!(records[0].message_id, "roocode:1760000000000:1001");
+        assert_eq!(
+            (
+                records[0].input_tokens,
+                records[0].cache_creation_input_tokens,
+                records[0].cache_read_input_tokens
+            ),
+            (100, 5, 7)
+        );
+        assert_eq!(records[0].reported_cost, Some(0.01));
+        assert_eq!(records[1].reported_cost, None);
+        assert!(records.iter().all(|r| r.project == "foo"));
+    }
+}
@@ -7,8 +7,9 @@ use super::{
     compute_provider_roots, discover_and_parse_with, discover_files, HomeFallback,
     Provider as ProviderDriver, XdgBase,
 };
+use crate::project::ProjectId;
 use crate::storage::Storage;
-use crate::types::{Provider, UsageRecord};
+use crate::types::{normalize_service_tier, Provider, UsageRecord};
 
 pub struct CodexProvider;
 
@@ -31,8 +32,7 @@ impl ProviderDriver for CodexProvider {
         let files = discover_files(&roots, "jsonl");
         discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
             let session_id = session_id_from_path(path);
-            let project = project_from_session_id(&session_id);
-            parse_jsonl_file(path, &session_id, &project)
+            parse_jsonl_file(path, &session_id)
         });
     }
 }
@@ -70,14 +70,15 @@ fn session_id_from_path(path: &Path) -> String {
         .to_string()
 }
 
-/// Project: first path component of session ID
-fn project_from_session_id(session_id: &str) -> String {
-    session_id
-        .split('/')
-        .next()
-        .filter(|s| !s.is_empty())
-        .unwrap_or("codex")
-        .to_string()
+/// Project from the working directory a `session_meta` or `turn_context`
+/// payload records.
+fn project_from_payload(parsed: &serde_json::Value) -> Option<ProjectId> {
+    parsed
+        .get("payload")
+        .and_then(|p| p.get("cwd"))
+        .and_then(|v| v.as_str())
+        .filter(|cwd| !cwd.is_empty())
+        .map(crate::project::resolve)
 }
 
 #[derive(Default)]
@@ -85,12 +86,13 @@ struct CumulativeTotals {
     input_tokens: u64,
     output_tokens: u64,
     cached_input_tokens: u64,
+    reasoning_output_tokens: u64,
 }
 
 /// Codex uses a two-pass approach within a single file: turn_context lines
 /// set the model, and token_count lines carry the actual usage data.
 /// This stateful iteration doesn't fit the generic parse_jsonl_lines utility.
-fn parse_jsonl_file(path: &Path, session_id: &str, project: &str) -> Vec<UsageRecord> {
+fn parse_jsonl_file(path: &Path, session_id: &str) -> Vec<UsageRecord> {
     let file = match std::fs::File::open(path) {
         Ok(f) => f,
         Err(_) => return Vec::new(),
@@ -99,7 +101,12 @@ fn parse_jsonl_file(path: &Path, session_id: &str, project: &str) -> Vec<UsageRe
     let reader = BufReader::new(file);
     let mut records = Vec::new();
     let mut last_model: Option<String> = None;
+    let mut last_tier: Option<String> = None;
+    let mut git_branch: Option<String> = None;
+    let mut project: Option<ProjectId> = None;
     let mut prev_totals = CumulativeTotals::default();
+    // Tool calls of the response whose token_count hasn't arrived yet.
+    let mut pending_calls: Vec<String> = Vec::new();
 
     for line in reader.lines() {
         let line = match line {
@@ -108,11 +115,47 @@ fn parse_jsonl_file(path: &Path, session_id: &str, project: &str) -> Vec<UsageRe
         };
 
         // Fast path: only parse lines relevant to us
+        if line.contains("\"session_meta\"") {
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&line) {
+                git_branch = parsed
+                    .get("payload")
+                    .and_then(|p| p.get("git"))
+                    .and_then(|g| g.get("branch"))
+                    .and_then(|v| v.as_str())
+                    .filter(|b| !b.is_empty())
+                    .map(String::from);
+                project = project_from_payload(&parsed);
+            }
+            continue;
+        }
+
         if line.contains("\"turn_context\"") {
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&line) {
                 if let Some(model) = extract_model_from_turn_context(&parsed) {
                     last_model = Some(model);
                 }
+                // Fall back to the turn's cwd if session_meta didn't carry one.
+                if project.is_none() {
+                    project = project_from_payload(&parsed);
+                }
+                if let Some(tier) = parsed
+                    .get("payload")
+                    .and_then(|p| p.get("service_tier"))
+                    .and_then(|v| v.as_str())
+                {
+                    last_tier = Some(normalize_service_tier(tier));
+                }
+            }
+            continue;
+        }
+
+        if line.contains("_call\"") {
+            if let Some(name) = serde_json::from_str::<serde_json::Value>(&line)
+                .ok()
+                .as_ref()
+                .and_then(tool_call_name)
+            {
+                pending_calls.push(name);
             }
             continue;
         }
@@ -126,9 +169,23 @@ fn parse_jsonl_file(path: &Path, session_id: &str, project: &str) -> Vec<UsageRe
             Err(_) => continue,
         };
 
-        if let Some(record) =
-            extract_token_event(&parsed, session_id, project, &last_model, &mut prev_totals)
+        if let Some(mut record) =
+            extract_token_event(&parsed, session_id, &last_model, &mut prev_totals)
         {
+            // The tier comes from the turn's config unless the event has its own.
+            record.service_tier = parsed
+                .get("payload")
+                .and_then(|p| p.get("info"))
+                .and_then(|i| i.get("service_tier"))
+                .and_then(|v| v.as_str())
+                .map(normalize_service_tier)
+                .or_else(|| last_tier.clone());
+            record.git_branch = git_branch.clone();
+            record.tool_calls = std::mem::take(&mut pending_calls);
+            if let Some(project) = &project {
+                record.project = project.name.clone();
+                record.project_path = project.path.clone();
+            }
             records.push(record);
         }
     }
@@ -136,6 +193,18 @@ fn parse_jsonl_file(path: &Path, session_id: &str, project: &str) -> Vec<UsageRe
     records
 }
 
+/// Tool name of a `response_item` that invokes one: function calls (MCP
+/// tools included), custom tools such as `apply_patch`, and the built-in
+/// local shell.
+fn tool_call_name(parsed: &serde_json::Value) -> Option<String> {
+    let payload = parsed.get("payload")?;
+    match payload.get("type")?.as_str()? {
+        "function_call" | "custom_tool_call" => Some(payload.get("name")?.as_str()?.to_string()),
+        "local_shell_call" => Some("local_shell".to_string()),
+        _ => None,
+    }
+}
+
 fn extract_model_from_turn_context(parsed: &serde_json::Value) -> Option<String> {
     let payload = parsed.get("payload")?;
 
@@ -172,7 +241,6 @@ fn extract_model_from_turn_context(parsed: &serde_json::Value) -> Option<String>
 fn extract_token_event(
     parsed: &serde_json::Value,
     session_id: &str,
-    project: &str,
     last_model: &Option<String>,
     prev_totals: &mut CumulativeTotals,
 ) -> Option<UsageRecord> {
@@ -210,7 +278,7 @@ fn extract_token_event(
         .unwrap_or_else(|| "unknown".to_string());
 
     // Delta calculation: prefer last_token_usage, fallback to total_token_usage subtraction
-    let (input, output, cached) = if let Some(last) = info.get("last_token_usage") {
+    let (input, output, cached, reasoning) = if let Some(last) = info.get("last_token_usage") {
         let input = last
             .get("input_tokens")
             .and_then(|v| v.as_u64())
@@ -224,7 +292,11 @@ fn extract_token_event(
             .or_else(|| last.get("cache_read_input_tokens"))
             .and_then(|v| v.as_u64())
             .unwrap_or(0);
-        (input, output, cached)
+        let reasoning = last
+            .get("reasoning_output_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        (input, output, cached, reasoning)
     } else if let Some(total) = info.get("total_token_usage") {
         let cur_input = total
             .get("input_tokens")
@@ -239,16 +311,22 @@ fn extract_token_event(
             .or_else(|| total.get("cache_read_input_tokens"))
             .and_then(|v| v.as_u64())
             .unwrap_or(0);
+        let cur_reasoning = total
+            .get("reasoning_output_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
 
         let input = cur_input.saturating_sub(prev_totals.input_tokens);
         let output = cur_output.saturating_sub(prev_totals.output_tokens);
         let cached = cur_cached.saturating_sub(prev_totals.cached_input_tokens);
+        let reasoning = cur_reasoning.saturating_sub(prev_totals.reasoning_output_tokens);
 
         prev_totals.input_tokens = cur_input;
         prev_totals.output_tokens = cur_output;
         prev_totals.cached_input_tokens = cur_cached;
+        prev_totals.reasoning_output_tokens = cur_reasoning;
 
-        (input, output, cached)
+        (input, output, cached, reasoning)
     } else {
         return None;
     };
@@ -264,14 +342,211 @@ fn extract_token_event(
         provider: Provider::Codex,
         session_id: session_id.to_string(),
         timestamp,
-        project: project.to_string(),
+        project: "codex".to_string(), // replaced by the session cwd's project
         model,
         message_id,
         request_id: String::new(),
         input_tokens: input,
-        output_tokens: output,
+        // OpenAI counts reasoning within output; tku keeps the classes apart.
+        output_tokens: output.saturating_sub(reasoning),
         cache_creation_input_tokens: 0,
         cache_read_input_tokens: cached,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: reasoning.min(output),
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: String::new(),
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         account_uuid: None,
+        reported_cost: None,
     })
 }
+
+/// One ChatGPT-plan quota window from a `token_count` event's `rate_limits`.
+#[derive(Debug, Clone)]
+pub struct RateLimitSnapshot {
+    pub observed_at: DateTime<Utc>,
+    pub window_minutes: u64,
+    pub used_percent: f64,
+    pub resets_at: DateTime<Utc>,
+}
+
+/// Quota snapshots from rollouts written since `since`, keeping the latest
+/// per window and reset. Codex only records these in its session files, so
+/// they're read directly rather than through the record cache.
+pub fn rate_limit_snapshots(since: DateTime<Utc>) -> Vec<RateLimitSnapshot> {
+    let mut all = Vec::new();
+    for file in discover_files(&compute_roots(), "jsonl") {
+        if file.mtime < since.timestamp() {
+            continue;
+        }
+        let Ok(f) = std::fs::File::open(&file.path) else {
+            continue;
+        };
+        for line in BufReader::new(f).lines().map_while(Result::ok) {
+            if !line.contains("\"rate_limits\"") {
+                continue;
+            }
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&line) {
+                all.extend(parse_rate_limits(&parsed));
+            }
+        }
+    }
+
+    // `resets_at` is derived from a relative `resets_in_seconds` on most
+    // versions, so the same reset jitters by a second or two across events.
+    all.sort_by_key(|s| (s.window_minutes, s.resets_at));
+    let mut latest: Vec<RateLimitSnapshot> = Vec::new();
+    for snap in all {
+        match latest.last_mut() {
+            Some(prev)
+                if prev.window_minutes == snap.window_minutes
+                    && (snap.resets_at - prev.resets_at).num_minutes() < 10 =>
+            {
+                if snap.observed_at > prev.observed_at {
+                    *prev = snap;
+                }
+            }
+            _ => latest.push(snap),
+        }
+    }
+    latest
+}
+
+/// `payload.rate_limits.{primary,secondary}` of a `token_count` event.
+fn parse_rate_limits(parsed: &serde_json::Value) -> Vec<RateLimitSnapshot> {
+    let Some(observed_at) = parsed
+        .get("timestamp")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+    else {
+        return Vec::new();
+    };
+    let Some(limits) = parsed.get("payload").and_then(|p| p.get("rate_limits")) else {
+        return Vec::new();
+    };
+
+    ["primary", "secondary"]
+        .iter()
+        .filter_map(|key| {
+            let window = limits.get(key)?;
+            let resets_at = match window.get("resets_at").and_then(|v| v.as_i64()) {
+                Some(secs) => DateTime::<Utc>::from_timestamp(secs, 0)?,
+                None => {
+                    let secs = window.get("resets_in_seconds").and_then(|v| v.as_i64())?;
+                    observed_at + chrono::Duration::seconds(secs)
+                }
+            };
+            Some(RateLimitSnapshot {
+                observed_at,
+                window_minutes: window.get("window_minutes").and_then(|v| v.as_u64())?,
+                used_percent: window.get("used_percent").and_then(|v| v.as_f64())?,
+                resets_at,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a rollout at `sessions/2026/10/15/` under a temp dir, parse it
+    /// the way discovery does, and clean up.
+    fn parse_rollout(name: &str, lines: &[serde_json::Value]) -> Vec<UsageRecord> {
+        let dir = std::env::temp_dir().join(format!("tku-codex-{name}-{}", std::process::id()));
+        let day = dir.join("sessions").join("2026").join("10").join("15");
+        std::fs::create_dir_all(&day).unwrap();
+        let path = day.join("rollout-2026-10-15T10-00-00-abc.jsonl");
+        let body: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(&path, body.join("\n")).unwrap();
+        let records = parse_jsonl_file(&path, &session_id_from_path(&path));
+        std::fs::remove_dir_all(&dir).unwrap();
+        records
+    }
+
+    fn token_count() -> serde_json::Value {
+        serde_json::json!({
+            "timestamp": "2026-10-15T10:00:05Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "token_count",
+                "info": {"last_token_usage": {"input_tokens": 100, "output_tokens": 20}}
+            }
+        })
+    }
+
+    #[test]
+    fn project_comes_from_session_cwd_not_the_date_folder() {
+        let records = parse_rollout(
+            "cwd",
+            &[
+                serde_json::json!({
+                    "timestamp": "2026-10-15T10:00:00Z",
+                    "type": "session_meta",
+                    "payload": {"id": "abc", "cwd": "/nonexistent/tku/my-app"}
+                }),
+                token_count(),
+            ],
+        );
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].session_id,
+            "2026/10/15/rollout-2026-10-15T10-00-00-abc"
+        );
+        assert_eq!(records[0].project, "my-app");
+        assert_eq!(records[0].project_path, "/nonexistent/tku/my-app");
+    }
+
+    #[test]
+    fn session_meta_git_branch_tags_every_record() {
+        let meta = |branch: &str| {
+            serde_json::json!({
+                "timestamp": "2026-10-15T10:00:00Z",
+                "type": "session_meta",
+                "payload": {
+                    "cwd": "/nonexistent/tku/my-app",
+                    "git": {"branch": branch, "commit_hash": "abc123"}
+                }
+            })
+        };
+
+        let records = parse_rollout("branch", &[meta("feature/x"), token_count()]);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].git_branch.as_deref(), Some("feature/x"));
+
+        // An empty branch counts as none.
+        let records = parse_rollout("detached", &[meta(""), token_count()]);
+        assert_eq!(records[0].git_branch, None);
+    }
+
+    #[test]
+    fn rate_limits_accept_relative_and_absolute_resets() {
+        let line = serde_json::json!({
+            "timestamp": "2026-10-15T10:00:00Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "token_count",
+                "rate_limits": {
+                    "primary": {"used_percent": 12.5, "window_minutes": 300, "resets_in_seconds": 3600},
+                    "secondary": {"used_percent": 40.0, "window_minutes": 10080, "resets_at": 1792144800}
+                }
+            }
+        });
+        let snaps = parse_rate_limits(&line);
+        assert_eq!(snaps.len(), 2);
+        assert_eq!(snaps[0].window_minutes, 300);
+        assert_eq!(
+            snaps[0].resets_at,
+            "2026-10-15T11:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(snaps[1].used_percent, 40.0);
+        assert_eq!(snaps[1].resets_at.timestamp(), 1792144800);
+    }
+}
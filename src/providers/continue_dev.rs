@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    compute_provider_roots, discover_and_parse_with, discover_files_with, parse_jsonl_lines,
+    HomeFallback, Provider as ProviderDriver, XdgBase,
+};
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+const TOKENS_FILE: &str = "tokensGenerated.jsonl";
+const AUTOCOMPLETE_FILE: &str = "autocomplete.jsonl";
+
+/// How far apart a completion's `autocomplete` event and the
+/// `tokensGenerated` event of the call that produced it may be.
+const AUTOCOMPLETE_MATCH_MS: i64 = 5_000;
+
+/// Session id prefixes separating tab-autocomplete traffic from chat/edit
+/// requests. Continue doesn't log sessions with its token events, so each
+/// kind is grouped per day: `autocomplete:2025-03-01`, `chat:2025-03-01`.
+const AUTOCOMPLETE_PREFIX: &str = "autocomplete";
+const CHAT_PREFIX: &str = "chat";
+
+/// Continue.dev logs one `tokensGenerated` event per LLM call to
+/// `~/.continue/dev_data/<schema>/tokensGenerated.jsonl`, for chat, edit and
+/// tab autocomplete alike.
+pub struct ContinueProvider;
+
+impl ProviderDriver for ContinueProvider {
+    fn id(&self) -> Provider {
+        Provider::Continue
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        compute_roots()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let files = discover_files_with(&compute_roots(), |p| {
+            p.file_name().is_some_and(|n| n == TOKENS_FILE)
+        })
+        .into_iter()
+        .map(|f| {
+            let autocomplete = f.path.with_file_name(AUTOCOMPLETE_FILE);
+            f.depending_on(&autocomplete)
+        })
+        .collect();
+        discover_and_parse_with(
+            self.name(),
+            files,
+            storage,
+            progress,
+            prune,
+            parse_tokens_file,
+        );
+    }
+}
+
+fn compute_roots() -> Vec<PathBuf> {
+    compute_provider_roots(
+        Some("CONTINUE_GLOBAL_DIR"),
+        &["dev_data"],
+        &[HomeFallback {
+            base: XdgBase::Home,
+            subpaths: &[".continue", "dev_data"],
+        }],
+    )
+}
+
+/// When each model served a tab autocomplete, from the sibling
+/// `autocomplete.jsonl`: Continue logs every completion it shows there, with
+/// the model's name. Timestamps in milliseconds, sorted per model.
+fn autocomplete_times(tokens_path: &Path) -> HashMap<String, Vec<i64>> {
+    let mut times: HashMap<String, Vec<i64>> = HashMap::new();
+    for (model, ts) in parse_jsonl_lines(
+        &tokens_path.with_file_name(AUTOCOMPLETE_FILE),
+        "\"modelName\"",
+        |line| {
+            let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
+            let model = parsed.get("modelName")?.as_str()?.to_string();
+            let ts = parsed
+                .get("timestamp")?
+                .as_str()?
+                .parse::<DateTime<Utc>>()
+                .ok()?;
+            Some((model, ts.timestamp_millis()))
+        },
+    ) {
+        times.entry(model).or_default().push(ts);
+    }
+    for ts in times.values_mut() {
+        ts.sort_unstable();
+    }
+    times
+}
+
+/// Token events don't say which feature made the call, so a call counts as
+/// autocomplete when its model showed a completion within
+/// [`AUTOCOMPLETE_MATCH_MS`] of it. The model alone isn't enough: the same
+/// model can serve chat too.
+fn is_autocomplete(times: &HashMap<String, Vec<i64>>, model: &str, ts: i64) -> bool {
+    let Some(times) = times.get(model) else {
+        return false;
+    };
+    let i = times.partition_point(|t| *t < ts - AUTOCOMPLETE_MATCH_MS);
+    times
+        .get(i)
+        .is_some_and(|t| *t <= ts + AUTOCOMPLETE_MATCH_MS)
+}
+
+/// Parse `tokensGenerated.jsonl`. Events from schema 0.1.0 carry no
+/// timestamp and are skipped, since there's no way to date them.
+fn parse_tokens_file(path: &Path) -> Vec<UsageRecord> {
+    let autocomplete = autocomplete_times(path);
+
+    parse_jsonl_lines(path, "\"generatedTokens\"", |line| {
+        let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
+        let timestamp = parsed
+            .get("timestamp")
+            .and_then(|v| v.as_str())?
+            .parse::<DateTime<Utc>>()
+            .ok()?;
+
+        let input = parsed
+            .get("promptTokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        let output = parsed
+            .get("generatedTokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        if input == 0 && output == 0 {
+            return None;
+        }
+
+        let model = parsed
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let kind = if is_autocomplete(&autocomplete, &model, timestamp.timestamp_millis()) {
+            AUTOCOMPLETE_PREFIX
+        } else {
+            CHAT_PREFIX
+        };
+        let ts = timestamp.to_rfc3339();
+
+        Some(UsageRecord {
+            provider: Provider::Continue,
+            session_id: format!("{kind}:{}", timestamp.format("%Y-%m-%d")),
+            timestamp,
+            project: "continue".to_string(),
+            message_id: format!("continue:{ts}:{model}:{input}:{output}"),
+            model,
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: None,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autocomplete_is_decided_per_call() {
+        let dir = std::env::temp_dir()
+            .join(format!("tku-continue-{}", std::process::id()))
+            .join("0.2.0");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(AUTOCOMPLETE_FILE),
+            concat!(
+                r#"{"timestamp":"2025-03-01T10:00:01.500Z","modelName":"qwen2.5-coder","completion":"x"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join(TOKENS_FILE),
+            concat!(
+                r#"{"timestamp":"2025-03-01T10:00:00.000Z","model":"qwen2.5-coder","promptTokens":300,"generatedTokens":12}"#,
+                "\n",
+                r#"{"timestamp":"2025-03-01T11:30:00.000Z","model":"qwen2.5-coder","promptTokens":900,"generatedTokens":200}"#,
+                "\n",
+                r#"{"model":"qwen2.5-coder","promptTokens":1,"generatedTokens":1}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let records = parse_tokens_file(&dir.join(TOKENS_FILE));
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        assert_eq!(records.len(), 2, "untimestamped events are skipped");
+        assert_eq!(records[0].session_id, "autocomplete:2025-03-01");
+        assert_eq!(records[1].session_id, "chat:2025-03-01");
+        assert_eq!(
+            (records[1].input_tokens, records[1].output_tokens),
+            (900, 200)
+        );
+    }
+
+    #[test]
+    fn autocomplete_match_window() {
+        let times = HashMap::from([("m".to_string(), vec![10_000, 50_000])]);
+        assert!(is_autocomplete(&times, "m", 10_000 - AUTOCOMPLETE_MATCH_MS));
+        assert!(is_autocomplete(&times, "m", 54_000));
+        assert!(!is_autocomplete(&times, "m", 30_000));
+        assert!(!is_autocomplete(&times, "other", 10_000));
+    }
+}
@@ -0,0 +1,253 @@
+use std::path::{Path, PathBuf};
+
+use super::{discover_and_parse_with, DiscoveredFile, Provider as ProviderDriver};
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Crush keeps a `.crush/crush.db` inside each project it runs in, so
+/// discovery walks the configured code roots like aider does. Each assistant
+/// message is a record; usage and the provider-reported cost come from its
+/// `sessions` row.
+pub struct CrushProvider;
+
+impl ProviderDriver for CrushProvider {
+    fn id(&self) -> Provider {
+        Provider::Crush
+    }
+
+    /// The databases are scattered across projects; watch them directly.
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        discover().into_iter().map(|f| f.path).collect()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        discover_and_parse_with(
+            self.name(),
+            discover(),
+            storage,
+            progress,
+            prune,
+            parse_crush_db,
+        );
+    }
+}
+
+/// Without the sqlite feature there's nothing to parse, so skip the walk
+/// (and keep empty cache entries out of storage; see llm.rs).
+#[cfg(feature = "sqlite")]
+fn discover() -> Vec<DiscoveredFile> {
+    super::discover_in_code_roots(&super::code_roots(), |p| {
+        p.file_name().is_some_and(|n| n == "crush.db")
+            && p.parent()
+                .and_then(|d| d.file_name())
+                .is_some_and(|n| n == ".crush")
+    })
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn discover() -> Vec<DiscoveredFile> {
+    Vec::new()
+}
+
+/// `<project>/.crush/crush.db` → `<project>`.
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+fn project_from_db_path(path: &Path) -> ProjectId {
+    match path
+        .parent()
+        .and_then(|d| d.parent())
+        .and_then(|d| d.to_str())
+    {
+        Some(dir) if !dir.is_empty() => crate::project::resolve(dir),
+        _ => ProjectId {
+            name: "crush".to_string(),
+            path: String::new(),
+        },
+    }
+}
+
+/// One record per assistant message, dated to the message and priced at
+/// its model.
+///
+/// Crush doesn't keep usage per message: the session's `prompt_tokens` and
+/// `completion_tokens` are overwritten with each response's usage, so only
+/// the latest response's tokens survive, and they go on the session's
+/// latest message. `cost` does accumulate over the session; it's spread
+/// evenly across the session's messages as their reported cost, which makes
+/// `--cost-basis reported` the accurate basis for Crush.
+#[cfg(feature = "sqlite")]
+fn parse_crush_db(db_path: &Path) -> Vec<UsageRecord> {
+    match rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+    {
+        Ok(conn) => read_messages(&conn, &project_from_db_path(db_path)),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn read_messages(conn: &rusqlite::Connection, project: &ProjectId) -> Vec<UsageRecord> {
+    use chrono::{DateTime, Utc};
+
+    let mut stmt = match conn.prepare(
+        "SELECT m.id, m.session_id, m.model, m.created_at,
+                s.prompt_tokens, s.completion_tokens, s.cost,
+                COUNT(*) OVER (PARTITION BY m.session_id),
+                ROW_NUMBER() OVER (PARTITION BY m.session_id
+                                   ORDER BY m.created_at DESC, m.rowid DESC)
+           FROM messages m JOIN sessions s ON s.id = m.session_id
+          WHERE m.role = 'assistant'
+          ORDER BY m.created_at, m.rowid",
+    ) {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+
+    let rows = match stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+            row.get::<_, Option<f64>>(6)?,
+            row.get::<_, i64>(7)?,
+            row.get::<_, i64>(8)?,
+        ))
+    }) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut records = Vec::new();
+
+    for row in rows {
+        let Ok((id, session_id, model, created_at, prompt, completion, cost, count, from_last)) =
+            row
+        else {
+            continue;
+        };
+
+        let (input, output) = if from_last == 1 {
+            (
+                prompt.unwrap_or(0).max(0) as u64,
+                completion.unwrap_or(0).max(0) as u64,
+            )
+        } else {
+            (0, 0)
+        };
+        let reported_cost = cost.filter(|c| *c > 0.0).map(|c| c / count.max(1) as f64);
+        if input == 0 && output == 0 && reported_cost.is_none() {
+            continue;
+        }
+
+        // Unix seconds; tolerate milliseconds in case that ever changes.
+        let secs = if created_at > 100_000_000_000 {
+            created_at / 1000
+        } else {
+            created_at
+        };
+        let Some(timestamp) = DateTime::<Utc>::from_timestamp(secs, 0) else {
+            continue;
+        };
+
+        records.push(UsageRecord {
+            provider: Provider::Crush,
+            session_id,
+            timestamp,
+            project: project.name.clone(),
+            model: model
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| "unknown".to_string()),
+            message_id: format!("crush:{id}"),
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: project.path.clone(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost,
+        });
+    }
+
+    records
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn parse_crush_db(_db_path: &Path) -> Vec<UsageRecord> {
+    Vec::new()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_carry_their_model_and_a_share_of_the_session_cost() {
+        let dir = std::env::temp_dir().join(format!("tku-crush-{}", std::process::id()));
+        let db_path = dir.join("app").join(".crush").join("crush.db");
+        std::fs::create_dir_all(db_path.parent().unwrap()).unwrap();
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (id TEXT PRIMARY KEY, prompt_tokens INTEGER,
+                 completion_tokens INTEGER, cost REAL, created_at INTEGER);
+             CREATE TABLE messages (id TEXT PRIMARY KEY, session_id TEXT, role TEXT,
+                 parts TEXT, model TEXT, created_at INTEGER);
+             INSERT INTO sessions VALUES ('s1', 1200, 80, 0.09, 1750000000),
+                                         ('s2', 0, 0, 0.0, 1750000000);
+             INSERT INTO messages VALUES
+                 ('m1', 's1', 'user', '[]', NULL, 1750000000),
+                 ('m2', 's1', 'assistant', '[]', 'gpt-4.1', 1750000010),
+                 ('m3', 's1', 'assistant', '[]', 'claude-sonnet-4-5', 1750000020),
+                 ('m4', 's1', 'assistant', '[]', 'claude-sonnet-4-5', 1750000030),
+                 ('m5', 's2', 'assistant', '[]', 'gpt-4.1', 1750000040);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let records = parse_crush_db(&db_path);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            records.len(),
+            3,
+            "user messages and empty sessions are skipped"
+        );
+        assert_eq!(
+            records.iter().map(|r| r.model.as_str()).collect::<Vec<_>>(),
+            ["gpt-4.1", "claude-sonnet-4-5", "claude-sonnet-4-5"]
+        );
+        assert_eq!(records[0].timestamp.timestamp(), 1750000010);
+        assert_eq!(records[0].message_id, "crush:m2");
+        assert!(records
+            .iter()
+            .all(|r| r.session_id == "s1" && r.project == "app"));
+
+        // Only the latest response's usage is on disk.
+        assert_eq!((records[0].input_tokens, records[0].output_tokens), (0, 0));
+        assert_eq!(
+            (records[2].input_tokens, records[2].output_tokens),
+            (1200, 80)
+        );
+
+        let reported: f64 = records.iter().filter_map(|r| r.reported_cost).sum();
+        assert!((reported - 0.09).abs() < 1e-9);
+        assert!((records[0].reported_cost.unwrap() - 0.03).abs() < 1e-9);
+    }
+}
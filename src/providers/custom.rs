@@ -0,0 +1,244 @@
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use super::{
+    discover_and_parse_with, discover_files_with, parse_jsonl_lines, Provider as ProviderDriver,
+};
+use crate::config::CustomProviderConfig;
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// A JSONL tool defined in `[[providers.custom]]`: files matching `root` are
+/// parsed line by line, and each line's fields are picked out with the
+/// configured JSON pointers.
+pub struct CustomProvider {
+    id: Provider,
+    root: PathBuf,
+    pattern: glob::Pattern,
+    config: CustomProviderConfig,
+}
+
+impl CustomProvider {
+    /// Build a provider from its config entry, warning about and skipping
+    /// entries with an unusable name or glob.
+    pub fn from_config(config: CustomProviderConfig) -> Option<Self> {
+        let Some(id) = Provider::custom(&config.name) else {
+            eprintln!(
+                "Warning: skipping custom provider {:?}: name must be lowercase [a-z0-9_-] and not a built-in tool",
+                config.name
+            );
+            return None;
+        };
+        let expanded = crate::config::expand_home(Path::new(&config.root));
+        let pattern = match glob::Pattern::new(&expanded.to_string_lossy()) {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!(
+                    "Warning: skipping custom provider {}: bad root glob: {e}",
+                    config.name
+                );
+                return None;
+            }
+        };
+        Some(Self {
+            id,
+            root: literal_prefix(&expanded),
+            pattern,
+            config,
+        })
+    }
+}
+
+impl ProviderDriver for CustomProvider {
+    fn id(&self) -> Provider {
+        self.id
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        vec![self.root.clone()]
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let files = discover_files_with(std::slice::from_ref(&self.root), |p| {
+            self.pattern.matches_path(p)
+        });
+        discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
+            parse_file(self.id, &self.config, path)
+        });
+    }
+}
+
+/// The directory part of a glob before its first wildcard component: the
+/// walk starts there and the full pattern filters what it finds.
+fn literal_prefix(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|c| match c {
+            Component::Normal(s) => !s.to_string_lossy().contains(['*', '?', '[']),
+            _ => true,
+        })
+        .collect()
+}
+
+fn parse_file(id: Provider, config: &CustomProviderConfig, path: &Path) -> Vec<UsageRecord> {
+    let file_stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    parse_jsonl_lines(path, &config.filter, |line| {
+        let parsed: serde_json::Value = serde_json::from_str(line).ok()?;
+
+        let string_at = |pointer: &Option<String>| {
+            pointer
+                .as_deref()
+                .and_then(|p| parsed.pointer(p))
+                .and_then(|v| match v {
+                    serde_json::Value::String(s) => Some(s.clone()),
+                    serde_json::Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .filter(|s| !s.is_empty())
+        };
+        let tokens_at = |pointer: &Option<String>| {
+            pointer
+                .as_deref()
+                .and_then(|p| parsed.pointer(p))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0)
+        };
+
+        let timestamp = parse_timestamp(parsed.pointer(&config.timestamp)?)?;
+        let input = tokens_at(&config.input_tokens);
+        let output = tokens_at(&config.output_tokens);
+        let cache_write = tokens_at(&config.cache_creation_input_tokens);
+        let cache_read = tokens_at(&config.cache_read_input_tokens);
+        let reasoning = tokens_at(&config.reasoning_tokens);
+        if input == 0 && output == 0 && cache_write == 0 && cache_read == 0 && reasoning == 0 {
+            return None;
+        }
+
+        let session_id = string_at(&config.session).unwrap_or_else(|| file_stem.clone());
+        let message_id = string_at(&config.message_id).unwrap_or_else(|| {
+            format!(
+                "{session_id}:{}:{input}:{output}",
+                timestamp.timestamp_millis()
+            )
+        });
+
+        // A path-valued project (cwd) is resolved like any other tool's;
+        // anything else is taken as the name.
+        let project = match string_at(&config.project).filter(|p| !p.is_empty()) {
+            Some(p) if p.contains(['/', '\\']) => crate::project::resolve(&p),
+            Some(p) => ProjectId {
+                name: p,
+                path: String::new(),
+            },
+            None => ProjectId {
+                name: id.as_str().to_string(),
+                path: String::new(),
+            },
+        };
+
+        Some(UsageRecord {
+            provider: id,
+            timestamp,
+            project: project.name,
+            model: string_at(&config.model).unwrap_or_else(|| "unknown".to_string()),
+            message_id: format!("{id}:{message_id}"),
+            session_id,
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_write,
+            cache_read_input_tokens: cache_read,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: reasoning,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: project.path,
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: config
+                .cost
+                .as_deref()
+                .and_then(|p| parsed.pointer(p))
+                .and_then(|v| v.as_f64())
+                .filter(|c| *c > 0.0),
+        })
+    })
+}
+
+/// RFC 3339 strings, or unix time as a number (seconds or milliseconds).
+fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::String(s) => s.parse::<DateTime<Utc>>().ok(),
+        serde_json::Value::Number(n) => {
+            let n = n.as_i64()?;
+            if n > 100_000_000_000 {
+                DateTime::<Utc>::from_timestamp_millis(n)
+            } else {
+                DateTime::<Utc>::from_timestamp(n, 0)
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_names_round_trip_and_cannot_shadow_builtins() {
+        let p = Provider::custom("my-agent").unwrap();
+        assert_eq!(p.as_str(), "my-agent");
+        assert_eq!("My-Agent".parse::<Provider>().unwrap(), p);
+        assert_eq!(
+            serde_json::to_string(&p).unwrap(),
+            serde_json::to_string("my-agent").unwrap()
+        );
+
+        assert_eq!(Provider::custom("claude"), None);
+        assert_eq!(Provider::custom("roo-code"), None);
+        assert_eq!(Provider::custom("../evil"), None);
+        assert_eq!(Provider::custom(""), None);
+    }
+
+    #[test]
+    fn only_registered_custom_names_parse() {
+        assert!("cladue".parse::<Provider>().is_err());
+        assert!("not-configured".parse::<Provider>().is_err());
+        // Cached records of a tool that left the config still load.
+        assert_eq!(
+            Provider::from_stored("not-configured").map(Provider::as_str),
+            Some("not-configured")
+        );
+        assert_eq!(Provider::from_stored("claude"), Some(Provider::Claude));
+    }
+
+    #[test]
+    fn walk_starts_at_glob_literal_prefix() {
+        assert_eq!(
+            literal_prefix(Path::new("/home/u/.tool/sessions/**/*.jsonl")),
+            PathBuf::from("/home/u/.tool/sessions")
+        );
+        assert_eq!(
+            literal_prefix(Path::new("/logs/usage.jsonl")),
+            PathBuf::from("/logs/usage.jsonl")
+        );
+    }
+}
@@ -178,6 +178,18 @@ fn parse_settings_file(path: &Path) -> Vec<UsageRecord> {
         output_tokens: output,
         cache_creation_input_tokens: cache_creation,
         cache_read_input_tokens: cache_read,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: 0,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: String::new(),
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         account_uuid: None,
+        reported_cost: None,
     }]
 }
@@ -11,15 +11,54 @@ use super::{
 use crate::storage::Storage;
 use crate::types::{Provider, UsageRecord};
 
-pub struct GeminiProvider;
+/// Gemini CLI and its forks (Qwen Code, …) write the same session JSON under
+/// `~/.<tool>/tmp/<projectHash>/chats/`. Each fork gets its own instance with
+/// its own provider id, home override and message type, so they're tracked
+/// separately while sharing the parser.
+pub struct GeminiProvider {
+    id: Provider,
+    /// Env var overriding the tool's home directory.
+    env_var: &'static str,
+    /// Home-relative session root used when `env_var` is unset.
+    home_subpaths: &'static [&'static str],
+    /// `type` values marking model responses. Forks that renamed the model
+    /// keep accepting `"gemini"` for sessions written before the rename.
+    message_types: &'static [&'static str],
+}
+
+impl GeminiProvider {
+    pub const GEMINI: Self = Self {
+        id: Provider::Gemini,
+        env_var: "GEMINI_HOME",
+        home_subpaths: &[".gemini", "tmp"],
+        message_types: &["gemini"],
+    };
+    pub const QWEN_CODE: Self = Self {
+        id: Provider::Qwen,
+        env_var: "QWEN_HOME",
+        home_subpaths: &[".qwen", "tmp"],
+        message_types: &["qwen", "gemini"],
+    };
+
+    fn compute_roots(&self) -> Vec<PathBuf> {
+        compute_provider_roots(
+            Some(self.env_var),
+            &["tmp"],
+            &[HomeFallback {
+                base: XdgBase::Home,
+                subpaths: self.home_subpaths,
+            }],
+        )
+    }
+}
 
 impl ProviderDriver for GeminiProvider {
     fn id(&self) -> Provider {
-        Provider::Gemini
+        self.id
     }
 
     fn root_dirs(&self) -> Vec<PathBuf> {
-        compute_roots()
+        self.compute_roots()
     }
 
     fn discover_and_parse(
@@ -28,25 +67,14 @@ impl ProviderDriver for GeminiProvider {
         progress: Option<&dyn Fn(usize, usize)>,
         prune: bool,
     ) {
-        let roots = compute_roots();
+        let roots = self.compute_roots();
         let files = discover_files(&roots, "json");
         discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
-            parse_session_file(path)
+            parse_session_file(path, self.id, self.message_types)
         });
     }
 }
 
-fn compute_roots() -> Vec<PathBuf> {
-    compute_provider_roots(
-        Some("GEMINI_HOME"),
-        &["tmp"],
-        &[HomeFallback {
-            base: XdgBase::Home,
-            subpaths: &[".gemini", "tmp"],
-        }],
-    )
-}
-
 #[derive(Deserialize)]
 struct GeminiSession {
     #[serde(rename = "sessionId")]
@@ -71,9 +99,10 @@ struct GeminiTokens {
     input: Option<u64>,
     output: Option<u64>,
     cached: Option<u64>,
+    thoughts: Option<u64>,
 }
 
-fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
+fn parse_session_file(path: &Path, id: Provider, message_types: &[&str]) -> Vec<UsageRecord> {
     let content = match std::fs::read_to_string(path) {
         Ok(c) => c,
         Err(_) => return Vec::new(),
@@ -88,7 +117,7 @@ fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
     let project = session
         .project_hash
         .filter(|s| !s.is_empty())
-        .unwrap_or_else(|| "gemini".to_string());
+        .unwrap_or_else(|| id.as_str().to_string());
 
     let file_mtime = std::fs::metadata(path)
         .ok()
@@ -108,7 +137,7 @@ fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
             Some(t) => t,
             None => continue,
         };
-        if msg_type != "gemini" {
+        if !message_types.contains(&msg_type.as_str()) {
             continue;
         }
 
@@ -125,8 +154,9 @@ fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
         let input = tokens.input.unwrap_or(0);
         let output = tokens.output.unwrap_or(0);
         let cached = tokens.cached.unwrap_or(0);
+        let thoughts = tokens.thoughts.unwrap_or(0);
 
-        if input == 0 && output == 0 {
+        if input == 0 && output == 0 && thoughts == 0 {
             continue;
         }
 
@@ -138,10 +168,10 @@ fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
             .unwrap_or_else(Utc::now);
 
         let msg_id_str = msg.id.as_deref().unwrap_or("unknown");
-        let message_id = format!("gemini:{session_id}:{msg_id_str}");
+        let message_id = format!("{}:{session_id}:{msg_id_str}", id.as_str());
 
         records.push(UsageRecord {
-            provider: Provider::Gemini,
+            provider: id,
             session_id: session_id.clone(),
             timestamp,
             project: project.clone(),
@@ -152,7 +182,19 @@ fn parse_session_file(path: &Path) -> Vec<UsageRecord> {
             output_tokens: output,
             cache_creation_input_tokens: 0,
             cache_read_input_tokens: cached,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: thoughts,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
             account_uuid: None,
+            reported_cost: None,
         });
     }
 
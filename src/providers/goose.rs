@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use super::{
+    compute_provider_roots, discover_and_parse_with, discover_files, HomeFallback,
+    Provider as ProviderDriver, XdgBase,
+};
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Goose keeps running token totals per session rather than per message, so
+/// each session yields a single record carrying its accumulated usage, dated
+/// to the session start. Older releases write one `<id>.jsonl` per session;
+/// newer ones keep everything in `sessions.db`.
+pub struct GooseProvider;
+
+impl ProviderDriver for GooseProvider {
+    fn id(&self) -> Provider {
+        Provider::Goose
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        compute_roots()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let roots = compute_roots();
+        let config_model = read_config_model();
+
+        // Parse SQLite db(s), collect session IDs for dedup against the
+        // JSONL files that goose migrated into the database.
+        let (sqlite_records, sqlite_db_paths) = collect_sqlite_records(&roots, &config_model);
+        let sqlite_ids: HashSet<String> = sqlite_records
+            .iter()
+            .map(|r| r.session_id.clone())
+            .collect();
+
+        // Insert SQLite records into storage (file-level caching via db path)
+        #[cfg(feature = "sqlite")]
+        for db_path in &sqlite_db_paths {
+            if let Some(df) = super::discovered_file(db_path) {
+                if !storage.is_cached(self.name(), db_path, df.mtime, df.size) {
+                    let db_records = sqlite_records.clone();
+                    storage.insert(self.name(), db_path, df.mtime, df.size, db_records);
+                }
+            }
+        }
+
+        let _ = &sqlite_db_paths; // suppress unused warning without sqlite
+
+        #[allow(unused_mut)]
+        let mut files = discover_files(&roots, "jsonl");
+
+        // Include db paths in the file list so prune doesn't remove them
+        #[cfg(feature = "sqlite")]
+        for db_path in &sqlite_db_paths {
+            if let Some(df) = super::discovered_file(db_path) {
+                files.push(df);
+            }
+        }
+
+        discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
+            // Skip db files in the parse phase — they're handled above
+            if path.extension().is_some_and(|ext| ext == "db") {
+                return Vec::new();
+            }
+            parse_session_file(path, &config_model)
+                .into_iter()
+                .filter(|r| !sqlite_ids.contains(&r.session_id))
+                .collect()
+        });
+    }
+}
+
+fn compute_roots() -> Vec<PathBuf> {
+    compute_provider_roots(
+        Some("GOOSE_PATH_ROOT"),
+        &["data/sessions"],
+        &[HomeFallback {
+            base: XdgBase::Data,
+            subpaths: &["goose", "sessions"],
+        }],
+    )
+}
+
+/// Default model from `~/.config/goose/config.yaml`. Sessions written before
+/// goose started recording the model per session fall back to this.
+fn read_config_model() -> String {
+    let config_path = compute_provider_roots(
+        Some("GOOSE_PATH_ROOT"),
+        &["config/config.yaml"],
+        &[HomeFallback {
+            base: XdgBase::Config,
+            subpaths: &["goose", "config.yaml"],
+        }],
+    );
+
+    for path in config_path {
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        // A flat `KEY: value` lookup is all we need; no YAML parser required.
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("GOOSE_MODEL:") {
+                let model = value.trim().trim_matches(['"', '\'']);
+                if !model.is_empty() {
+                    return model.to_string();
+                }
+            }
+        }
+    }
+
+    "unknown".to_string()
+}
+
+/// First line of a legacy session file.
+#[derive(Deserialize)]
+struct SessionMetadata {
+    working_dir: Option<String>,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    accumulated_input_tokens: Option<u64>,
+    accumulated_output_tokens: Option<u64>,
+}
+
+fn project_from_working_dir(working_dir: Option<&str>) -> ProjectId {
+    match working_dir.filter(|d| !d.is_empty()) {
+        Some(dir) => crate::project::resolve(dir),
+        None => ProjectId {
+            name: "goose".to_string(),
+            path: String::new(),
+        },
+    }
+}
+
+/// Parse a legacy `<session-id>.jsonl`: a metadata line followed by one line
+/// per message. Messages carry `created` (unix seconds) but no usage; the
+/// first one dates the session.
+fn parse_session_file(path: &Path, config_model: &str) -> Vec<UsageRecord> {
+    let file = match std::fs::File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Vec::new(),
+    };
+    let mut lines = BufReader::new(file).lines();
+
+    let meta: SessionMetadata = match lines
+        .next()
+        .and_then(|l| l.ok())
+        .and_then(|l| serde_json::from_str(&l).ok())
+    {
+        Some(m) => m,
+        None => return Vec::new(),
+    };
+
+    let created = lines
+        .map_while(|l| l.ok())
+        .filter(|l| l.contains("\"created\""))
+        .find_map(|l| {
+            let parsed: serde_json::Value = serde_json::from_str(&l).ok()?;
+            parsed.get("created").and_then(|v| v.as_i64())
+        })
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0));
+    let Some(timestamp) = created else {
+        return Vec::new();
+    };
+
+    let session_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    session_record(
+        session_id,
+        timestamp,
+        project_from_working_dir(meta.working_dir.as_deref()),
+        config_model.to_string(),
+        meta.accumulated_input_tokens.or(meta.input_tokens),
+        meta.accumulated_output_tokens.or(meta.output_tokens),
+    )
+    .into_iter()
+    .collect()
+}
+
+fn session_record(
+    session_id: String,
+    timestamp: DateTime<Utc>,
+    project: ProjectId,
+    model: String,
+    input: Option<u64>,
+    output: Option<u64>,
+) -> Option<UsageRecord> {
+    let input = input.unwrap_or(0);
+    let output = output.unwrap_or(0);
+    if input == 0 && output == 0 {
+        return None;
+    }
+
+    Some(UsageRecord {
+        provider: Provider::Goose,
+        message_id: format!("goose:{session_id}"),
+        session_id,
+        timestamp,
+        project: project.name,
+        model,
+        request_id: String::new(),
+        input_tokens: input,
+        output_tokens: output,
+        cache_creation_input_tokens: 0,
+        cache_read_input_tokens: 0,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: 0,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: project.path,
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
+        account_uuid: None,
+        reported_cost: None,
+    })
+}
+
+/// Always parse SQLite dbs to get records + paths (for dedup and prune).
+#[cfg(feature = "sqlite")]
+fn collect_sqlite_records(
+    roots: &[PathBuf],
+    config_model: &str,
+) -> (Vec<UsageRecord>, Vec<PathBuf>) {
+    let mut all_records = Vec::new();
+    let mut db_paths = Vec::new();
+
+    for root in roots {
+        let db_path = root.join("sessions.db");
+        if !db_path.exists() {
+            continue;
+        }
+
+        let records = parse_sqlite_db(&db_path, config_model);
+        if !records.is_empty() {
+            all_records.extend(records);
+            db_paths.push(db_path);
+        }
+    }
+
+    (all_records, db_paths)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn collect_sqlite_records(
+    _roots: &[PathBuf],
+    _config_model: &str,
+) -> (Vec<UsageRecord>, Vec<PathBuf>) {
+    (Vec::new(), Vec::new())
+}
+
+/// Parse goose's `sessions.db`. Column sets have grown across releases
+/// (`accumulated_*`, `model_config_json`), so select only what exists.
+#[cfg(feature = "sqlite")]
+fn parse_sqlite_db(db_path: &Path, config_model: &str) -> Vec<UsageRecord> {
+    let conn = match rusqlite::Connection::open_with_flags(
+        db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    ) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    let columns: HashSet<String> =
+        match conn.prepare("SELECT name FROM pragma_table_info('sessions')") {
+            Ok(mut stmt) => stmt
+                .query_map([], |row| row.get(0))
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                .unwrap_or_default(),
+            Err(_) => return Vec::new(),
+        };
+    if !columns.contains("id") || !columns.contains("created_at") {
+        return Vec::new();
+    }
+    let col = |name: &str| {
+        if columns.contains(name) {
+            name.to_string()
+        } else {
+            "NULL".to_string()
+        }
+    };
+
+    let sql = format!(
+        "SELECT id, {}, created_at, {}, {}, {}, {}, {} FROM sessions",
+        col("working_dir"),
+        col("accumulated_input_tokens"),
+        col("accumulated_output_tokens"),
+        col("input_tokens"),
+        col("output_tokens"),
+        col("model_config_json"),
+    );
+    let mut stmt = match conn.prepare(&sql) {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+
+    let rows = match stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<i64>>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+            row.get::<_, Option<i64>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+        ))
+    }) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    let to_u64 = |v: Option<i64>| v.map(|n| n.max(0) as u64);
+    let mut records = Vec::new();
+
+    for row in rows {
+        let Ok((id, working_dir, created_at, acc_in, acc_out, input, output, model_json)) = row
+        else {
+            continue;
+        };
+        let Some(timestamp) = parse_db_timestamp(&created_at) else {
+            continue;
+        };
+        let model = model_json
+            .and_then(|j| serde_json::from_str::<serde_json::Value>(&j).ok())
+            .and_then(|v| {
+                v.get("model_name")
+                    .and_then(|m| m.as_str())
+                    .map(String::from)
+            })
+            .unwrap_or_else(|| config_model.to_string());
+
+        records.extend(session_record(
+            id,
+            timestamp,
+            project_from_working_dir(working_dir.as_deref()),
+            model,
+            to_u64(acc_in).or(to_u64(input)),
+            to_u64(acc_out).or(to_u64(output)),
+        ));
+    }
+
+    records
+}
+
+/// `created_at` is SQLite's `CURRENT_TIMESTAMP` (UTC, no offset) in current
+/// releases; accept RFC 3339 as well.
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+fn parse_db_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    s.parse::<DateTime<Utc>>().ok().or_else(|| {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+            .ok()
+            .map(|n| n.and_utc())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tku-goose-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn db_timestamps_in_sqlite_and_rfc3339_form() {
+        let expected = "2026-03-01T12:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(parse_db_timestamp("2026-03-01 12:30:00"), Some(expected));
+        assert_eq!(parse_db_timestamp("2026-03-01T12:30:00Z"), Some(expected));
+        assert_eq!(
+            parse_db_timestamp("2026-03-01T14:30:00+02:00"),
+            Some(expected)
+        );
+        assert_eq!(parse_db_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn session_record_skips_empty_sessions() {
+        let ts = "2026-03-01T12:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let project = project_from_working_dir(None);
+        let r =
+            session_record("s1".into(), ts, project.clone(), "m".into(), Some(5), None).unwrap();
+        assert_eq!(r.message_id, "goose:s1");
+        assert_eq!(r.project, "goose");
+        assert_eq!((r.input_tokens, r.output_tokens), (5, 0));
+        assert!(session_record("s2".into(), ts, project, "m".into(), Some(0), None).is_none());
+    }
+
+    #[test]
+    fn legacy_session_prefers_accumulated_totals() {
+        let path = scratch("20260301_1.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"working_dir":"/nonexistent/tku/goose-app","input_tokens":10,"output_tokens":2,"accumulated_input_tokens":300,"accumulated_output_tokens":40}"#,
+                "\n",
+                r#"{"role":"user","created":1772368200,"content":[]}"#,
+                "\n",
+                r#"{"role":"assistant","created":1772368260,"content":[]}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let records = parse_session_file(&path, "gpt-4o");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!(r.session_id, "20260301_1");
+        assert_eq!(r.model, "gpt-4o");
+        assert_eq!(r.project, "goose-app");
+        assert_eq!((r.input_tokens, r.output_tokens), (300, 40));
+        assert_eq!(r.timestamp.timestamp(), 1772368200);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sessions_db_with_and_without_newer_columns() {
+        let path = scratch("sessions.db");
+        let _ = std::fs::remove_file(&path);
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE sessions (id TEXT, working_dir TEXT, created_at TEXT,
+                 input_tokens INTEGER, output_tokens INTEGER, model_config_json TEXT);
+             INSERT INTO sessions VALUES
+                 ('a', '/nonexistent/tku/app', '2026-03-01 12:30:00', 100, 20,
+                  '{\"model_name\":\"claude-sonnet-4-5\"}'),
+                 ('b', NULL, '2026-03-02 08:00:00', 50, 5, NULL),
+                 ('c', NULL, '2026-03-03 08:00:00', 0, 0, NULL);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let records = parse_sqlite_db(&path, "fallback");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].model, "claude-sonnet-4-5");
+        assert_eq!(records[0].project, "app");
+        assert_eq!(records[0].input_tokens, 100);
+        assert_eq!(records[1].model, "fallback");
+        assert_eq!(records[1].project, "goose");
+    }
+}
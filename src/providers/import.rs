@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use clap::ValueEnum;
+
+use super::{discover_and_parse_with, discover_files, Provider as ProviderDriver};
+use crate::atomic_write::atomic_write;
+use crate::paths;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Billing-side usage exports accepted by `tku import-usage`.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ImportSource {
+    /// Anthropic Console usage export
+    AnthropicConsole,
+    /// OpenAI platform usage export
+    Openai,
+}
+
+impl ImportSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::AnthropicConsole => "anthropic-console",
+            Self::Openai => "openai",
+        }
+    }
+
+    /// Imports are stored as `<source>-<hash>.csv`; recover the source.
+    fn from_file_name(name: &str) -> Option<Self> {
+        [Self::AnthropicConsole, Self::Openai]
+            .into_iter()
+            .find(|s| name.starts_with(&format!("{}-", s.as_str())))
+    }
+}
+
+/// Usage imported from API billing exports. `import-usage` copies each CSV
+/// into the data dir; scans then parse those copies like any other source.
+pub struct ImportProvider;
+
+impl ProviderDriver for ImportProvider {
+    fn id(&self) -> Provider {
+        Provider::Import
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        paths::imports_dir().into_iter().collect()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        let files = discover_files(&self.root_dirs(), "csv");
+        discover_and_parse_with(self.name(), files, storage, progress, prune, |path| {
+            let source = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(ImportSource::from_file_name);
+            match (source, std::fs::read(path)) {
+                (Some(source), Ok(data)) => parse_export(source, &data),
+                _ => Vec::new(),
+            }
+        });
+    }
+}
+
+/// Validate `file` as a `source` export and store a copy for future scans.
+///
+/// The copy is named after a hash of the content, so importing the same
+/// export twice is a no-op. Overlapping exports (say, month-to-date taken
+/// twice) produce rows with the same stable message ids, which
+/// `dedup::dedup` collapses. Returns the number of usage rows and whether
+/// the file had already been imported.
+pub fn import_usage(source: ImportSource, file: &Path) -> Result<(usize, bool)> {
+    let data = std::fs::read(file).with_context(|| format!("read {}", file.display()))?;
+    let rows = parse_export(source, &data).len();
+    if rows == 0 {
+        bail!(
+            "no usage rows recognized in {} (expected a {} export)",
+            file.display(),
+            source.as_str()
+        );
+    }
+
+    let dir = paths::imports_dir().context("no data directory available")?;
+    std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+    let dest = dir.join(format!("{}-{:016x}.csv", source.as_str(), fnv1a(&data)));
+    if dest.exists() {
+        return Ok((rows, true));
+    }
+    atomic_write(&dest, &data, None).with_context(|| format!("write {}", dest.display()))?;
+    Ok((rows, false))
+}
+
+/// FNV-1a: a content key that, unlike `DefaultHasher`, is stable across
+/// Rust releases.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Header aliases per field, in order of preference. Export layouts have
+/// shifted over time, so columns are matched by name rather than position.
+struct Columns {
+    date: &'static [&'static str],
+    model: &'static [&'static str],
+    project: &'static [&'static str],
+    api_key: &'static [&'static str],
+    input: &'static [&'static str],
+    /// All listed columns are summed (e.g. 5m and 1h cache writes).
+    cache_write: &'static [&'static str],
+    /// The 1-hour TTL share of `cache_write`, when the export splits it.
+    cache_write_1h: &'static [&'static str],
+    cache_read: &'static [&'static str],
+    output: &'static [&'static str],
+    cost: &'static [&'static str],
+    /// Whether `input` already includes cache reads (OpenAI) and needs them
+    /// subtracted to match tku's disjoint token classes.
+    input_includes_cached: bool,
+}
+
+const ANTHROPIC_COLUMNS: Columns = Columns {
+    date: &["usage_date_utc", "usage_date", "date"],
+    model: &["model_version", "model"],
+    project: &["workspace", "workspace_name"],
+    api_key: &["api_key", "api_key_name", "api_key_id"],
+    input: &["uncached_input_tokens", "input_tokens"],
+    cache_write: &[
+        "cache_write_5m_tokens",
+        "cache_write_1h_tokens",
+        "cache_creation_input_tokens",
+        "cache_write_tokens",
+    ],
+    cache_write_1h: &["cache_write_1h_tokens"],
+    cache_read: &["cache_read_tokens", "cache_read_input_tokens"],
+    output: &["output_tokens"],
+    cost: &["cost_usd", "cost"],
+    input_includes_cached: false,
+};
+
+const OPENAI_COLUMNS: Columns = Columns {
+    date: &["start_time_iso", "start_time", "date"],
+    model: &["model", "snapshot_id"],
+    project: &["project_name", "project_id"],
+    api_key: &["api_key_name", "api_key_id"],
+    input: &["input_tokens", "n_context_tokens_total"],
+    cache_write: &[],
+    cache_write_1h: &[],
+    cache_read: &["input_cached_tokens", "n_cached_context_tokens_total"],
+    output: &["output_tokens", "n_generated_tokens_total"],
+    cost: &["cost_usd", "amount_value"],
+    input_includes_cached: true,
+};
+
+/// Parse an export into one record per row, dated to the row's day (or
+/// bucket start). Rows without a date, model or any tokens are skipped.
+fn parse_export(source: ImportSource, data: &[u8]) -> Vec<UsageRecord> {
+    let columns = match source {
+        ImportSource::AnthropicConsole => &ANTHROPIC_COLUMNS,
+        ImportSource::Openai => &OPENAI_COLUMNS,
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+    let header: HashMap<String, usize> = match reader.headers() {
+        Ok(h) => h
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.to_lowercase(), i))
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let Ok(row) = row else {
+            continue;
+        };
+        let cell = |names: &[&str]| {
+            names
+                .iter()
+                .filter_map(|n| header.get(*n).and_then(|&i| row.get(i)))
+                .find(|v| !v.is_empty())
+        };
+        let count = |names: &[&str]| {
+            names
+                .iter()
+                .filter_map(|n| header.get(*n).and_then(|&i| row.get(i)))
+                .filter_map(|v| v.parse::<f64>().ok())
+                .map(|v| v.max(0.0) as u64)
+                .sum::<u64>()
+        };
+        // Only the first matching column counts for input/output/read;
+        // summing aliases would double-count exports that carry both.
+        let first_count = |names: &[&str]| {
+            names
+                .iter()
+                .find(|n| header.contains_key(**n))
+                .map_or(0, |n| count(&[n]))
+        };
+
+        let Some(timestamp) = cell(columns.date).and_then(parse_date) else {
+            continue;
+        };
+        let Some(model) = cell(columns.model) else {
+            continue;
+        };
+
+        let cache_read = first_count(columns.cache_read);
+        let mut input = first_count(columns.input);
+        if columns.input_includes_cached {
+            input = input.saturating_sub(cache_read);
+        }
+        let cache_write = count(columns.cache_write);
+        let cache_write_1h = first_count(columns.cache_write_1h);
+        let output = first_count(columns.output);
+        if input == 0 && output == 0 && cache_write == 0 && cache_read == 0 {
+            continue;
+        }
+
+        let project = cell(columns.project).unwrap_or("default").to_string();
+        let api_key = cell(columns.api_key).unwrap_or("default");
+
+        // Stable identity: the row's date plus every non-numeric cell
+        // (workspace, key, model, usage type, tier, ...). Other numbers are
+        // the usage itself and may be revised in a later export of the same
+        // period. The date goes in parsed, since OpenAI gives unix seconds.
+        let date = timestamp.to_rfc3339();
+        let identity: Vec<&str> = std::iter::once(date.as_str())
+            .chain(
+                row.iter()
+                    .filter(|v| !v.is_empty() && v.parse::<f64>().is_err()),
+            )
+            .collect();
+
+        records.push(UsageRecord {
+            provider: Provider::Import,
+            session_id: format!("{}:{api_key}", source.as_str()),
+            timestamp,
+            project,
+            model: model.to_string(),
+            message_id: format!("{}:{}", source.as_str(), identity.join("|")),
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_write,
+            cache_read_input_tokens: cache_read,
+            cache_creation_1h_input_tokens: cache_write_1h,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: cell(columns.cost).and_then(|c| c.parse::<f64>().ok()),
+        });
+    }
+
+    records
+}
+
+/// `2025-03-01`, RFC 3339, `2025-03-01 00:00:00` or unix seconds.
+fn parse_date(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = s.parse::<DateTime<Utc>>() {
+        return Some(dt);
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return d.and_hms_opt(0, 0, 0).map(|n| n.and_utc());
+    }
+    if let Ok(n) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        return Some(n.and_utc());
+    }
+    s.parse::<i64>()
+        .ok()
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anthropic_export_sums_cache_writes() {
+        let csv = "usage_date_utc,model_version,api_key,workspace,usage_type,uncached_input_tokens,cache_write_5m_tokens,cache_write_1h_tokens,cache_read_tokens,output_tokens\n\
+                   2025-03-01,claude-sonnet-4-5,ci-key,Default,standard,100,20,5,400,50\n\
+                   2025-03-01,claude-sonnet-4-5,ci-key,Default,batch,0,0,0,0,0\n";
+        let records = parse_export(ImportSource::AnthropicConsole, csv.as_bytes());
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!(
+            (
+                r.input_tokens,
+                r.cache_creation_input_tokens,
+                r.cache_creation_1h_input_tokens,
+                r.cache_read_input_tokens
+            ),
+            (100, 25, 5, 400)
+        );
+        assert_eq!(r.project, "Default");
+        assert_eq!(r.session_id, "anthropic-console:ci-key");
+    }
+
+    #[test]
+    fn openai_input_excludes_cached_and_ids_ignore_counts() {
+        let a =
+            "start_time_iso,project_name,model,input_tokens,input_cached_tokens,output_tokens\n\
+                 2025-03-01T00:00:00+00:00,web,gpt-5,1000,600,80\n";
+        let b =
+            "start_time_iso,project_name,model,input_tokens,input_cached_tokens,output_tokens\n\
+                 2025-03-01T00:00:00+00:00,web,gpt-5,1200,600,90\n";
+        let ra = parse_export(ImportSource::Openai, a.as_bytes());
+        let rb = parse_export(ImportSource::Openai, b.as_bytes());
+        assert_eq!(
+            (ra[0].input_tokens, ra[0].cache_read_input_tokens),
+            (400, 600)
+        );
+        assert_eq!(ra[0].message_id, rb[0].message_id);
+    }
+
+    #[test]
+    fn unix_dated_rows_on_different_days_keep_distinct_ids() {
+        let csv = "start_time,end_time,project_id,model,input_tokens,output_tokens\n\
+                   1740787200,1740873600,proj_1,gpt-5,100,10\n\
+                   1740873600,1740960000,proj_1,gpt-5,100,10\n";
+        let records = parse_export(ImportSource::Openai, csv.as_bytes());
+        assert_eq!(records.len(), 2);
+        assert_ne!(records[0].message_id, records[1].message_id);
+        assert_eq!(crate::dedup::dedup(records).len(), 2);
+    }
+}
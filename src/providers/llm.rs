@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+use super::{
+    compute_provider_roots, discover_and_parse_with, HomeFallback, Provider as ProviderDriver,
+    XdgBase,
+};
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Simon Willison's `llm` CLI logs every prompt/response pair to a single
+/// sqlite database. The db is the cache unit: any new prompt bumps its
+/// mtime/size and the whole file is re-read.
+pub struct LlmProvider;
+
+impl ProviderDriver for LlmProvider {
+    fn id(&self) -> Provider {
+        Provider::Llm
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        compute_roots()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        // Without the sqlite feature there's nothing to parse. Don't hand the
+        // db to the cache either: an empty entry keyed on the current
+        // mtime/size would stick around after rebuilding with sqlite.
+        #[cfg(not(feature = "sqlite"))]
+        let files = Vec::new();
+        #[cfg(feature = "sqlite")]
+        let files = compute_roots()
+            .iter()
+            .filter_map(|root| super::discovered_file(&root.join("logs.db")))
+            .collect();
+
+        discover_and_parse_with(self.name(), files, storage, progress, prune, parse_logs_db);
+    }
+}
+
+fn compute_roots() -> Vec<PathBuf> {
+    compute_provider_roots(
+        Some("LLM_USER_PATH"),
+        &[],
+        &[
+            HomeFallback {
+                base: XdgBase::Config,
+                subpaths: &["io.datasette.llm"],
+            },
+            HomeFallback {
+                base: XdgBase::Home,
+                subpaths: &["Library", "Application Support", "io.datasette.llm"],
+            },
+        ],
+    )
+}
+
+#[cfg(feature = "sqlite")]
+fn parse_logs_db(db_path: &Path) -> Vec<UsageRecord> {
+    match rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+    {
+        Ok(conn) => read_responses(&conn),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Read the `responses` table. Rows predating token logging (llm < 0.19)
+/// have NULL counts and are skipped.
+#[cfg(feature = "sqlite")]
+fn read_responses(conn: &rusqlite::Connection) -> Vec<UsageRecord> {
+    use chrono::NaiveDateTime;
+
+    let mut stmt = match conn.prepare(
+        "SELECT id, model, datetime_utc, conversation_id,
+                input_tokens, output_tokens, token_details
+           FROM responses
+          WHERE input_tokens IS NOT NULL OR output_tokens IS NOT NULL",
+    ) {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+
+    let rows = match stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<i64>>(4)?,
+            row.get::<_, Option<i64>>(5)?,
+            row.get::<_, Option<String>>(6)?,
+        ))
+    }) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut records = Vec::new();
+
+    for row in rows {
+        let Ok((id, model, datetime, conversation_id, input, output, details)) = row else {
+            continue;
+        };
+
+        // `datetime_utc` is naive ISO 8601 in UTC, e.g. 2025-03-01T10:00:00.123456
+        let Some(timestamp) = NaiveDateTime::parse_from_str(&datetime, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .map(|n| n.and_utc())
+        else {
+            continue;
+        };
+
+        let mut input = input.unwrap_or(0).max(0) as u64;
+        let output = output.unwrap_or(0).max(0) as u64;
+        if input == 0 && output == 0 {
+            continue;
+        }
+
+        let (cache_write, cache_read, cached_in_input) = details
+            .as_deref()
+            .and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok())
+            .map(|d| cache_tokens(&d))
+            .unwrap_or_default();
+        if cached_in_input {
+            input = input.saturating_sub(cache_read);
+        }
+
+        records.push(UsageRecord {
+            provider: Provider::Llm,
+            session_id: conversation_id.unwrap_or_else(|| id.clone()),
+            timestamp,
+            project: "llm".to_string(),
+            model,
+            message_id: format!("llm:{id}"),
+            request_id: String::new(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_write,
+            cache_read_input_tokens: cache_read,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: None,
+        });
+    }
+
+    records
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn parse_logs_db(_db_path: &Path) -> Vec<UsageRecord> {
+    Vec::new()
+}
+
+/// Cache classes from `token_details`, which holds whatever the plugin's
+/// usage block carried beyond input/output. Anthropic reports cache reads
+/// separately from input; OpenAI's `cached_tokens` are a subset of input,
+/// flagged by the third tuple element so the caller can subtract them.
+#[cfg(feature = "sqlite")]
+fn cache_tokens(details: &serde_json::Value) -> (u64, u64, bool) {
+    let get = |v: &serde_json::Value, key: &str| v.get(key).and_then(|n| n.as_u64());
+
+    let cache_write = get(details, "cache_creation_input_tokens").unwrap_or(0);
+    if let Some(read) = get(details, "cache_read_input_tokens") {
+        return (cache_write, read, false);
+    }
+    let openai_cached = details
+        .get("prompt_tokens_details")
+        .and_then(|p| get(p, "cached_tokens"))
+        .or_else(|| get(details, "cached_tokens"));
+    match openai_cached {
+        Some(read) => (cache_write, read, true),
+        None => (cache_write, 0, false),
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_map_to_records_with_cache_classes() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE responses (id TEXT, model TEXT, datetime_utc TEXT,
+                   conversation_id TEXT, input_tokens INTEGER, output_tokens INTEGER,
+                   token_details TEXT);
+               INSERT INTO responses VALUES
+                   ('r1', 'claude-sonnet-4-5', '2025-03-01T10:00:00.123456', 'c1', 100, 20,
+                    '{"cache_creation_input_tokens": 30, "cache_read_input_tokens": 50}'),
+                   ('r2', 'gpt-4o', '2025-03-01T11:00:00', NULL, 100, 10,
+                    '{"prompt_tokens_details": {"cached_tokens": 60}}'),
+                   ('r3', 'gpt-4o', '2025-03-01T12:00:00', 'c2', NULL, NULL, NULL),
+                   ('r4', 'gpt-4o', 'not a date', 'c2', 5, 5, NULL);"#,
+        )
+        .unwrap();
+
+        let records = read_responses(&conn);
+        assert_eq!(records.len(), 2);
+
+        let anthropic = &records[0];
+        assert_eq!(anthropic.session_id, "c1");
+        assert_eq!(anthropic.message_id, "llm:r1");
+        assert_eq!(
+            anthropic.timestamp.to_rfc3339(),
+            "2025-03-01T10:00:00.123456+00:00"
+        );
+        assert_eq!(
+            (
+                anthropic.input_tokens,
+                anthropic.cache_creation_input_tokens,
+                anthropic.cache_read_input_tokens
+            ),
+            (100, 30, 50)
+        );
+
+        // OpenAI's cached tokens are part of the input count.
+        let openai = &records[1];
+        assert_eq!(openai.session_id, "r2");
+        assert_eq!(
+            (openai.input_tokens, openai.cache_read_input_tokens),
+            (40, 60)
+        );
+    }
+}
@@ -1,12 +1,22 @@
+pub mod aider;
 pub mod amp;
 pub mod claude;
+pub mod cline;
 pub mod codex;
+pub mod continue_dev;
+pub mod crush;
+pub mod custom;
 pub mod droid;
 pub mod gemini;
+pub mod goose;
+pub mod import;
 pub mod kimi;
+pub mod llm;
 pub mod openclaw;
 pub mod opencode;
 pub mod pi;
+pub mod plugin;
+pub mod zed;
 
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
@@ -67,17 +77,53 @@ pub fn all_watch_paths() -> Vec<PathBuf> {
 }
 
 pub fn all_providers() -> Vec<Box<dyn Provider>> {
-    vec![
+    let mut providers: Vec<Box<dyn Provider>> = vec![
         Box::new(claude::ClaudeProvider),
         Box::new(codex::CodexProvider),
         Box::new(pi::PiProvider),
         Box::new(amp::AmpProvider),
         Box::new(opencode::OpenCodeProvider),
-        Box::new(gemini::GeminiProvider),
+        Box::new(gemini::GeminiProvider::GEMINI),
+        Box::new(gemini::GeminiProvider::QWEN_CODE),
         Box::new(droid::DroidProvider),
         Box::new(openclaw::OpenClawProvider),
         Box::new(kimi::KimiProvider),
-    ]
+        Box::new(cline::ClineProvider::CLINE),
+        Box::new(cline::ClineProvider::ROO_CODE),
+        Box::new(cline::ClineProvider::KILO_CODE),
+        Box::new(aider::AiderProvider),
+        Box::new(goose::GooseProvider),
+        Box::new(llm::LlmProvider),
+        Box::new(zed::ZedProvider),
+        Box::new(crush::CrushProvider),
+        Box::new(continue_dev::ContinueProvider),
+        Box::new(import::ImportProvider),
+    ];
+
+    // Custom tools and plugins share one namespace; the first definition of
+    // a name wins so two sources never prune each other's cache entries.
+    let config = crate::config::load_config().providers;
+    let mut extra: Vec<Box<dyn Provider>> = Vec::new();
+    for c in config.custom {
+        if let Some(p) = custom::CustomProvider::from_config(c) {
+            extra.push(Box::new(p));
+        }
+    }
+    for c in config.plugin {
+        if let Some(p) = plugin::PluginProvider::from_config(c) {
+            extra.push(Box::new(p));
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    for p in extra {
+        if seen.insert(p.id()) {
+            providers.push(p);
+        } else {
+            eprintln!("Warning: duplicate provider name {:?}, skipping", p.name());
+        }
+    }
+
+    providers
 }
 
 pub(crate) struct DiscoveredFile {
@@ -86,6 +132,19 @@ pub(crate) struct DiscoveredFile {
     pub size: u64,
 }
 
+impl DiscoveredFile {
+    /// Also key the cache on `other`, for parsers that read a second file
+    /// alongside this one, so a change to either reparses. A missing `other`
+    /// leaves the key as is.
+    pub(crate) fn depending_on(mut self, other: &Path) -> Self {
+        if let Some(dep) = discovered_file(other) {
+            self.mtime = self.mtime.max(dep.mtime);
+            self.size += dep.size;
+        }
+        self
+    }
+}
+
 pub(crate) fn discovered_file(path: &Path) -> Option<DiscoveredFile> {
     let meta = std::fs::metadata(path).ok()?;
     let mtime = meta
@@ -139,6 +198,89 @@ where
     files
 }
 
+/// Directory names never worth descending into when searching code roots.
+/// These are build outputs and dependency trees, which can hold hundreds of
+/// thousands of entries and never contain agent session files.
+const CODE_ROOT_SKIP_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    ".venv",
+    "venv",
+    "__pycache__",
+    "dist",
+    "build",
+];
+
+/// How deep below a code root we look for repositories. Covers layouts like
+/// `~/git/<org>/<repo>/.crush/crush.db` without walking entire home trees.
+const CODE_ROOT_MAX_DEPTH: usize = 6;
+
+/// Default code roots under `$HOME`, mirroring the folder markers that
+/// Claude's project-name heuristic recognises.
+const DEFAULT_CODE_ROOTS: &[&str] = &["git", "src", "code", "projects", "repos", "workspace"];
+
+/// Roots to search for per-repo session files (tools that write their state
+/// next to the code instead of into a central directory). Taken from
+/// `[providers] code_roots` in the config, else the defaults under `$HOME`.
+pub(crate) fn code_roots() -> Vec<PathBuf> {
+    let configured = crate::config::load_config().providers.code_roots;
+    if !configured.is_empty() {
+        return configured
+            .iter()
+            .map(|p| crate::config::expand_home(p))
+            .collect();
+    }
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+        return Vec::new();
+    };
+    DEFAULT_CODE_ROOTS.iter().map(|d| home.join(d)).collect()
+}
+
+/// Like [`discover_files_with`], but bounded for code roots: depth-limited
+/// and skipping [`CODE_ROOT_SKIP_DIRS`].
+pub(crate) fn discover_in_code_roots<F>(roots: &[PathBuf], accept: F) -> Vec<DiscoveredFile>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut files = Vec::new();
+
+    for entry in walk_code_roots(roots) {
+        if entry.file_type().is_file() && accept(entry.path()) {
+            if let Some(df) = discovered_file(entry.path()) {
+                files.push(df);
+            }
+        }
+    }
+
+    files
+}
+
+/// Every directory under the code roots, with the same bounds as
+/// [`discover_in_code_roots`].
+pub(crate) fn dirs_in_code_roots(roots: &[PathBuf]) -> Vec<PathBuf> {
+    walk_code_roots(roots)
+        .filter(|e| e.file_type().is_dir())
+        .map(walkdir::DirEntry::into_path)
+        .collect()
+}
+
+fn walk_code_roots(roots: &[PathBuf]) -> impl Iterator<Item = walkdir::DirEntry> + '_ {
+    roots.iter().filter(|r| r.exists()).flat_map(|root| {
+        WalkDir::new(root)
+            .follow_links(false)
+            .max_depth(CODE_ROOT_MAX_DEPTH)
+            .into_iter()
+            .filter_entry(|e| {
+                !(e.file_type().is_dir()
+                    && e.file_name()
+                        .to_str()
+                        .is_some_and(|n| CODE_ROOT_SKIP_DIRS.contains(&n)))
+            })
+            .filter_map(|e| e.ok())
+    })
+}
+
 pub(crate) fn discover_and_parse_with<F>(
     name: &str,
     files: Vec<DiscoveredFile>,
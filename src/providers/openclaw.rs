@@ -180,6 +180,18 @@ fn extract_message(
         output_tokens: output,
         cache_creation_input_tokens: cache_write,
         cache_read_input_tokens: cache_read,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: 0,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: String::new(),
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         account_uuid: None,
+        reported_cost: None,
     })
 }
@@ -7,6 +7,7 @@ use super::{
     compute_provider_roots, discover_and_parse_with, discover_files_with, DiscoveredFile,
     HomeFallback, Provider as ProviderDriver, XdgBase,
 };
+use crate::project::ProjectId;
 use crate::storage::Storage;
 use crate::types::{Provider, UsageRecord};
 
@@ -122,7 +123,7 @@ fn classify_storage_file(path: &Path) -> Option<StorageKind> {
     None
 }
 
-fn build_session_projects(session_files: &[DiscoveredFile]) -> HashMap<String, String> {
+fn build_session_projects(session_files: &[DiscoveredFile]) -> HashMap<String, ProjectId> {
     let mut map = HashMap::new();
     for f in session_files {
         if let Some((session_id, project)) = parse_session_file(&f.path) {
@@ -136,7 +137,7 @@ fn build_session_projects(session_files: &[DiscoveredFile]) -> HashMap<String, S
 #[cfg(feature = "sqlite")]
 fn collect_sqlite_records(
     roots: &[PathBuf],
-    session_projects: &HashMap<String, String>,
+    session_projects: &HashMap<String, ProjectId>,
 ) -> (Vec<UsageRecord>, Vec<PathBuf>) {
     let mut all_records = Vec::new();
     let mut db_paths = Vec::new();
@@ -163,7 +164,7 @@ fn collect_sqlite_records(
 #[cfg(not(feature = "sqlite"))]
 fn collect_sqlite_records(
     _roots: &[PathBuf],
-    _session_projects: &HashMap<String, String>,
+    _session_projects: &HashMap<String, ProjectId>,
 ) -> (Vec<UsageRecord>, Vec<PathBuf>) {
     (Vec::new(), Vec::new())
 }
@@ -179,26 +180,42 @@ fn compute_roots() -> Vec<PathBuf> {
     )
 }
 
-fn parse_session_file(path: &Path) -> Option<(String, String)> {
+fn project_for(session_projects: &HashMap<String, ProjectId>, session_id: &str) -> ProjectId {
+    session_projects
+        .get(session_id)
+        .cloned()
+        .unwrap_or_else(|| ProjectId {
+            name: "opencode".to_string(),
+            path: String::new(),
+        })
+}
+
+fn parse_session_file(path: &Path) -> Option<(String, ProjectId)> {
     let content = std::fs::read_to_string(path).ok()?;
     let parsed: serde_json::Value = serde_json::from_str(&content).ok()?;
 
     let session_id = parsed.get("id")?.as_str()?.to_string();
 
-    // Use directory basename as project name, fall back to projectID
-    let project = parsed
-        .get("directory")
-        .and_then(|v| v.as_str())
-        .and_then(|d| d.rsplit('/').next())
-        .filter(|s| !s.is_empty())
-        .or_else(|| parsed.get("projectID").and_then(|v| v.as_str()))
-        .unwrap_or("opencode")
-        .to_string();
+    // Resolve the session's directory, falling back to projectID
+    let project = match parsed.get("directory").and_then(|v| v.as_str()) {
+        Some(dir) if !dir.is_empty() => crate::project::resolve(dir),
+        _ => ProjectId {
+            name: parsed
+                .get("projectID")
+                .and_then(|v| v.as_str())
+                .unwrap_or("opencode")
+                .to_string(),
+            path: String::new(),
+        },
+    };
 
     Some((session_id, project))
 }
 
-fn parse_message_file(path: &Path, session_projects: &HashMap<String, String>) -> Vec<UsageRecord> {
+fn parse_message_file(
+    path: &Path,
+    session_projects: &HashMap<String, ProjectId>,
+) -> Vec<UsageRecord> {
     let content = match std::fs::read_to_string(path) {
         Ok(c) => c,
         Err(_) => return Vec::new(),
@@ -217,7 +234,10 @@ fn parse_message_file(path: &Path, session_projects: &HashMap<String, String>) -
 
 /// Parse a single opencode.db SQLite database (OpenCode 1.2+).
 #[cfg(feature = "sqlite")]
-fn parse_sqlite_db(db_path: &Path, session_projects: &HashMap<String, String>) -> Vec<UsageRecord> {
+fn parse_sqlite_db(
+    db_path: &Path,
+    session_projects: &HashMap<String, ProjectId>,
+) -> Vec<UsageRecord> {
     let conn = match rusqlite::Connection::open_with_flags(
         db_path,
         rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
@@ -259,10 +279,9 @@ fn parse_sqlite_db(db_path: &Path, session_projects: &HashMap<String, String>) -
         };
 
         if let Some(mut record) = extract_record_from_data(&parsed, &id, &session_id) {
-            record.project = session_projects
-                .get(&session_id)
-                .cloned()
-                .unwrap_or_else(|| "opencode".to_string());
+            let project = project_for(session_projects, &session_id);
+            record.project = project.name;
+            record.project_path = project.path;
             records.push(record);
         }
     }
@@ -286,6 +305,10 @@ fn extract_record_from_data(
     let tokens = parsed.get("tokens")?;
     let input = tokens.get("input").and_then(|v| v.as_u64()).unwrap_or(0);
     let output = tokens.get("output").and_then(|v| v.as_u64()).unwrap_or(0);
+    let reasoning = tokens
+        .get("reasoning")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
 
     let cache_read = tokens
         .get("cache")
@@ -298,7 +321,7 @@ fn extract_record_from_data(
         .and_then(|v| v.as_u64())
         .unwrap_or(0);
 
-    if input == 0 && output == 0 {
+    if input == 0 && output == 0 && reasoning == 0 {
         return None;
     }
 
@@ -314,13 +337,34 @@ fn extract_record_from_data(
         output_tokens: output,
         cache_creation_input_tokens: cache_write,
         cache_read_input_tokens: cache_read,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: reasoning,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: String::new(),
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         account_uuid: None,
+        reported_cost: reported_cost(parsed),
     })
 }
 
+/// opencode stores what the upstream charged per message; zero means the
+/// provider didn't report a cost (e.g. subscription-backed models).
+fn reported_cost(parsed: &serde_json::Value) -> Option<f64> {
+    parsed
+        .get("cost")
+        .and_then(|v| v.as_f64())
+        .filter(|c| *c > 0.0)
+}
+
 fn extract_record(
     parsed: &serde_json::Value,
-    session_projects: &HashMap<String, String>,
+    session_projects: &HashMap<String, ProjectId>,
 ) -> Option<UsageRecord> {
     // Required fields
     let message_id = parsed.get("id")?.as_str()?.to_string();
@@ -342,6 +386,10 @@ fn extract_record(
     let tokens = parsed.get("tokens")?;
     let input = tokens.get("input").and_then(|v| v.as_u64()).unwrap_or(0);
     let output = tokens.get("output").and_then(|v| v.as_u64()).unwrap_or(0);
+    let reasoning = tokens
+        .get("reasoning")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
 
     let cache_read = tokens
         .get("cache")
@@ -355,20 +403,17 @@ fn extract_record(
         .unwrap_or(0);
 
     // Skip zero-token messages
-    if input == 0 && output == 0 {
+    if input == 0 && output == 0 && reasoning == 0 {
         return None;
     }
 
-    let project = session_projects
-        .get(&session_id)
-        .cloned()
-        .unwrap_or_else(|| "opencode".to_string());
+    let project = project_for(session_projects, &session_id);
 
     Some(UsageRecord {
         provider: Provider::OpenCode,
         session_id,
         timestamp,
-        project,
+        project: project.name,
         model,
         message_id,
         request_id: String::new(),
@@ -376,6 +421,18 @@ fn extract_record(
         output_tokens: output,
         cache_creation_input_tokens: cache_write,
         cache_read_input_tokens: cache_read,
+        cache_creation_1h_input_tokens: 0,
+        reasoning_tokens: reasoning,
+        web_search_requests: 0,
+        web_fetch_requests: 0,
+        service_tier: None,
+        agent: None,
+        git_branch: None,
+        project_path: project.path,
+        event: None,
+        failed_turn: false,
+        tool_calls: Vec::new(),
         account_uuid: None,
+        reported_cost: reported_cost(parsed),
     })
 }
@@ -0,0 +1,416 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Provider as ProviderDriver;
+use crate::atomic_write::atomic_write;
+use crate::config::PluginConfig;
+use crate::paths;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 60;
+const COMMAND_PREFIX: &str = "tku-provider-";
+
+/// An external command that prints usage as NDJSON on stdout.
+///
+/// Protocol: tku runs `<command> [args] [--since <cursor>]`. Each output
+/// line is either a record (`UsageRecord` field names; `timestamp` is
+/// required, everything else defaults) or `{"cursor": "..."}`. The last
+/// cursor seen is passed back as `--since` on the next run, so the plugin
+/// only has to print what's new. Without a cursor every run is a full run.
+///
+/// Each run's records are cached as one storage segment keyed by the cursor
+/// it was started from; segments accumulate until a full run replaces them.
+pub struct PluginProvider {
+    id: Provider,
+    config: PluginConfig,
+}
+
+impl PluginProvider {
+    pub fn from_config(config: PluginConfig) -> Option<Self> {
+        let name = config.name.clone().unwrap_or_else(|| {
+            let file = config
+                .command
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            file.strip_prefix(COMMAND_PREFIX)
+                .map(String::from)
+                .unwrap_or(file)
+        });
+        let Some(id) = Provider::custom(&name) else {
+            eprintln!(
+                "Warning: skipping provider plugin {name:?}: name must be lowercase [a-z0-9_-] and not a built-in tool"
+            );
+            return None;
+        };
+        Some(Self { id, config })
+    }
+
+    fn segment_key(&self, since: Option<&str>) -> PathBuf {
+        match since {
+            Some(cursor) => PathBuf::from(format!("plugin:{}?since={cursor}", self.id)),
+            None => PathBuf::from(format!("plugin:{}", self.id)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PluginState {
+    cursor: Option<String>,
+    segments: Vec<PathBuf>,
+}
+
+fn load_state(name: &str) -> PluginState {
+    paths::plugin_state_file(name)
+        .and_then(|p| std::fs::read(p).ok())
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(name: &str, state: &PluginState) {
+    let Some(path) = paths::plugin_state_file(name) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_vec(state) {
+        if let Err(e) = atomic_write(&path, &data, None) {
+            eprintln!("tku: plugin {name}: failed to save state: {e}");
+        }
+    }
+}
+
+impl ProviderDriver for PluginProvider {
+    fn id(&self) -> Provider {
+        self.id
+    }
+
+    /// Plugins aren't file-backed; there's nothing to watch.
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        _prune: bool,
+    ) {
+        let name = self.name();
+        let mut state = load_state(name);
+
+        // A cursor only means something while the records fetched before it
+        // are still cached; after a cache wipe, start over with a full run.
+        if state
+            .segments
+            .iter()
+            .any(|key| !storage.is_cached(name, key, 0, 0))
+        {
+            state = PluginState::default();
+        }
+
+        match run_plugin(&self.config, state.cursor.as_deref()) {
+            Ok(output) => {
+                if output.malformed > 0 {
+                    eprintln!(
+                        "tku: plugin {name}: skipped {} malformed line(s) ({})",
+                        output.malformed,
+                        output.first_error.unwrap_or_default()
+                    );
+                }
+                let key = self.segment_key(state.cursor.as_deref());
+                if state.cursor.is_none() {
+                    state.segments.clear();
+                }
+                let records = output
+                    .records
+                    .into_iter()
+                    .map(|r| r.into_record(self.id))
+                    .collect();
+                storage.insert(name, &key, 0, 0, records);
+                if !state.segments.contains(&key) {
+                    state.segments.push(key);
+                }
+                state.cursor = output.cursor;
+                save_state(name, &state);
+            }
+            Err(e) => eprintln!("tku: plugin {name}: {e}"),
+        }
+
+        // The segment list is authoritative for this provider, so always
+        // drop segments a full run has superseded, regardless of --prune.
+        storage.prune(name, &state.segments);
+
+        if let Some(cb) = progress {
+            cb(1, 1);
+        }
+    }
+}
+
+/// One record line. Field names follow `UsageRecord`; the provider is
+/// always the plugin's own name.
+#[derive(Deserialize)]
+struct PluginRecord {
+    timestamp: DateTime<Utc>,
+    #[serde(default)]
+    session_id: String,
+    #[serde(default)]
+    project: String,
+    #[serde(default)]
+    project_path: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    message_id: String,
+    #[serde(default)]
+    request_id: String,
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    reasoning_tokens: u64,
+    #[serde(default)]
+    reported_cost: Option<f64>,
+}
+
+impl PluginRecord {
+    fn into_record(self, id: Provider) -> UsageRecord {
+        let or_default = |s: String, default: &str| {
+            if s.is_empty() {
+                default.to_string()
+            } else {
+                s
+            }
+        };
+        let session_id = or_default(self.session_id, id.as_str());
+        let message_id = if self.message_id.is_empty() && self.request_id.is_empty() {
+            format!(
+                "{session_id}:{}:{}:{}",
+                self.timestamp.timestamp_millis(),
+                self.input_tokens,
+                self.output_tokens
+            )
+        } else {
+            self.message_id
+        };
+
+        UsageRecord {
+            provider: id,
+            session_id,
+            timestamp: self.timestamp,
+            project: or_default(self.project, id.as_str()),
+            model: or_default(self.model, "unknown"),
+            message_id,
+            request_id: self.request_id,
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: self.reasoning_tokens,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: self.project_path,
+            event: None,
+            failed_turn: false,
+            tool_calls: Vec::new(),
+            account_uuid: None,
+            reported_cost: self.reported_cost,
+        }
+    }
+}
+
+#[derive(Default)]
+struct PluginOutput {
+    records: Vec<PluginRecord>,
+    cursor: Option<String>,
+    malformed: usize,
+    first_error: Option<String>,
+}
+
+fn parse_output(stdout: impl Read) -> PluginOutput {
+    #[derive(Deserialize)]
+    struct CursorLine {
+        cursor: String,
+    }
+
+    let mut output = PluginOutput::default();
+    for (i, line) in BufReader::new(stdout).lines().enumerate() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<PluginRecord>(line) {
+            Ok(record) => output.records.push(record),
+            Err(record_err) => match serde_json::from_str::<CursorLine>(line) {
+                Ok(c) => output.cursor = Some(c.cursor),
+                Err(_) => {
+                    output.malformed += 1;
+                    output
+                        .first_error
+                        .get_or_insert_with(|| format!("line {}: {record_err}", i + 1));
+                }
+            },
+        }
+    }
+    output
+}
+
+/// Run the plugin to completion or until its timeout. Failures leave the
+/// cursor and cached segments untouched, so the next run retries them.
+fn run_plugin(config: &PluginConfig, since: Option<&str>) -> Result<PluginOutput, String> {
+    let command = crate::config::expand_home(&config.command);
+    let timeout = Duration::from_secs(config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+    let mut cmd = Command::new(&command);
+    cmd.args(&config.args);
+    if let Some(since) = since {
+        cmd.arg("--since").arg(since);
+    }
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {e}", command.display()))?;
+
+    // Drain both pipes on their own threads so a chatty plugin can't block
+    // on a full pipe while we wait for it to exit.
+    let stdout = child.stdout.take().ok_or("no stdout")?;
+    let stdout_reader = std::thread::spawn(move || parse_output(stdout));
+    let stderr = child.stderr.take().ok_or("no stderr")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stderr.take(64 * 1024).read_to_string(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("wait failed: {e}")),
+        }
+    };
+
+    let output = stdout_reader
+        .join()
+        .map_err(|_| "stdout reader panicked".to_string())?;
+    if !status.success() {
+        let stderr = stderr_reader.join().unwrap_or_default();
+        let detail = stderr.lines().last().unwrap_or("").trim();
+        return Err(if detail.is_empty() {
+            format!("exited with {status}")
+        } else {
+            format!("exited with {status}: {detail}")
+        });
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_separates_records_cursor_and_malformed_lines() {
+        let out = parse_output(
+            concat!(
+                "{\"timestamp\":\"2025-03-01T10:00:00Z\",\"model\":\"m\",\"input_tokens\":5}\n",
+                "\n",
+                "garbage\n",
+                "{\"cursor\":\"a\"}\n",
+                "{\"cursor\":\"b\"}\n",
+            )
+            .as_bytes(),
+        );
+        assert_eq!(out.records.len(), 1);
+        assert_eq!(out.cursor.as_deref(), Some("b"));
+        assert_eq!(out.malformed, 1);
+        assert!(out.first_error.unwrap().starts_with("line 3:"));
+    }
+
+    /// A plugin written as an inline `sh` script; `--since` lands in `$1`/`$2`.
+    #[cfg(unix)]
+    fn script(body: &str, timeout_secs: u64) -> PluginConfig {
+        PluginConfig {
+            command: PathBuf::from("sh"),
+            name: Some("script".to_string()),
+            args: vec!["-c".to_string(), body.to_string(), "script".to_string()],
+            timeout_secs: Some(timeout_secs),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cursor_round_trips_through_since() {
+        let plugin = script(
+            r#"
+            if [ "$1" = "--since" ]; then from="$2"; else from="start"; fi
+            echo "{\"timestamp\":\"2025-03-01T10:00:00Z\",\"session_id\":\"$from\"}"
+            echo "{\"cursor\":\"after-$from\"}"
+            "#,
+            10,
+        );
+
+        let full = run_plugin(&plugin, None).unwrap();
+        assert_eq!(full.records[0].session_id, "start");
+        assert_eq!(full.cursor.as_deref(), Some("after-start"));
+
+        let next = run_plugin(&plugin, full.cursor.as_deref()).unwrap();
+        assert_eq!(next.records[0].session_id, "after-start");
+        assert_eq!(next.cursor.as_deref(), Some("after-after-start"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn nonzero_exit_is_reported_with_the_last_stderr_line() {
+        let plugin = script(
+            r#"
+            echo '{"timestamp":"2025-03-01T10:00:00Z"}'
+            echo "warming up" >&2
+            echo "token expired" >&2
+            exit 3
+            "#,
+            10,
+        );
+        let err = run_plugin(&plugin, None).err().unwrap();
+        assert!(err.contains('3'), "{err}");
+        assert!(err.ends_with(": token expired"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plugin_is_killed_on_timeout() {
+        let plugin = script("exec sleep 30", 1);
+        let started = Instant::now();
+        let err = run_plugin(&plugin, None).err().unwrap();
+        assert_eq!(err, "timed out after 1s");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
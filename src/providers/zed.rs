@@ -0,0 +1,284 @@
+use std::path::{Path, PathBuf};
+
+use super::{
+    compute_provider_roots, discover_and_parse_with, HomeFallback, Provider as ProviderDriver,
+    XdgBase,
+};
+use crate::project::ProjectId;
+use crate::storage::Storage;
+use crate::types::{Provider, UsageRecord};
+
+/// Zed's agent panel keeps every thread as one row of `threads.db`, the
+/// payload being (usually zstd-compressed) JSON with per-request token
+/// usage. The db is the cache unit, like the `llm` provider's `logs.db`.
+pub struct ZedProvider;
+
+impl ProviderDriver for ZedProvider {
+    fn id(&self) -> Provider {
+        Provider::Zed
+    }
+
+    fn root_dirs(&self) -> Vec<PathBuf> {
+        compute_roots()
+    }
+
+    fn discover_and_parse(
+        &self,
+        storage: &mut dyn Storage,
+        progress: Option<&dyn Fn(usize, usize)>,
+        prune: bool,
+    ) {
+        // Without the sqlite feature there's nothing to parse; see llm.rs for
+        // why the db isn't cached as empty either.
+        #[cfg(not(feature = "sqlite"))]
+        let files = Vec::new();
+        #[cfg(feature = "sqlite")]
+        let files = compute_roots()
+            .iter()
+            .filter_map(|root| super::discovered_file(&root.join("threads.db")))
+            .collect();
+
+        discover_and_parse_with(
+            self.name(),
+            files,
+            storage,
+            progress,
+            prune,
+            parse_threads_db,
+        );
+    }
+}
+
+fn compute_roots() -> Vec<PathBuf> {
+    compute_provider_roots(
+        None,
+        &[],
+        &[
+            HomeFallback {
+                base: XdgBase::Data,
+                subpaths: &["zed", "threads"],
+            },
+            HomeFallback {
+                base: XdgBase::Home,
+                subpaths: &["Library", "Application Support", "Zed", "threads"],
+            },
+        ],
+    )
+}
+
+#[cfg(feature = "sqlite")]
+fn parse_threads_db(db_path: &Path) -> Vec<UsageRecord> {
+    let conn = match rusqlite::Connection::open_with_flags(
+        db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    ) {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut stmt = match conn.prepare("SELECT id, updated_at, data_type, data FROM threads") {
+        Ok(s) => s,
+        Err(_) => return Vec::new(),
+    };
+
+    let rows = match stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Vec<u8>>(3)?,
+        ))
+    }) {
+        Ok(r) => r,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut records = Vec::new();
+
+    for row in rows {
+        let Ok((thread_id, updated_at, data_type, data)) = row else {
+            continue;
+        };
+        let Some(json) = decode_payload(&data_type, &data) else {
+            continue;
+        };
+        let Ok(thread) = serde_json::from_slice::<serde_json::Value>(&json) else {
+            continue;
+        };
+        records.extend(extract_records(&thread, &thread_id, &updated_at));
+    }
+
+    records
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn parse_threads_db(_db_path: &Path) -> Vec<UsageRecord> {
+    Vec::new()
+}
+
+/// Payloads are stored either as plain JSON or zstd-compressed JSON. The
+/// decompressed size is capped like any other source file.
+#[cfg(feature = "sqlite")]
+fn decode_payload(data_type: &str, data: &[u8]) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    match data_type {
+        "json" => Some(data.to_vec()),
+        "zstd" => {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(data).ok()?;
+            let mut out = Vec::new();
+            decoder
+                .take(super::MAX_FILE_BYTES)
+                .read_to_end(&mut out)
+                .ok()?;
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+/// One record per model request in the thread.
+///
+/// Zed doesn't timestamp individual requests, so every request is dated to
+/// the thread's start (the initial project snapshot), falling back to the
+/// thread's last update. Dating to the start keeps a record's day stable as
+/// the thread grows.
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+fn extract_records(
+    thread: &serde_json::Value,
+    thread_id: &str,
+    updated_at: &str,
+) -> Vec<UsageRecord> {
+    use chrono::{DateTime, Utc};
+
+    let Some(timestamp) = thread
+        .get("initial_project_snapshot")
+        .and_then(|s| s.get("timestamp"))
+        .and_then(|v| v.as_str())
+        .or(Some(updated_at))
+        .and_then(|ts| ts.parse::<DateTime<Utc>>().ok())
+    else {
+        return Vec::new();
+    };
+
+    let model = thread
+        .get("model")
+        .and_then(|m| m.get("model"))
+        .and_then(|v| v.as_str())
+        // Zed exposes extended thinking as a `-thinking` model variant;
+        // it's billed as the base model.
+        .map(|m| m.strip_suffix("-thinking").unwrap_or(m).to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let project = thread
+        .get("initial_project_snapshot")
+        .and_then(|s| s.get("worktree_snapshots"))
+        .and_then(|w| w.as_array())
+        .and_then(|w| w.first())
+        .and_then(|w| w.get("worktree_path"))
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.is_empty())
+        .map(crate::project::resolve)
+        .unwrap_or_else(|| ProjectId {
+            name: "zed".to_string(),
+            path: String::new(),
+        });
+
+    // Current threads key usage by the user message that triggered the
+    // request; older ones keep a plain array in request order.
+    let usages: Vec<(String, &serde_json::Value)> = match thread.get("request_token_usage") {
+        Some(serde_json::Value::Object(map)) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Some(serde_json::Value::Array(arr)) => arr
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let get =
+        |usage: &serde_json::Value, key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+
+    usages
+        .into_iter()
+        .filter_map(|(key, usage)| {
+            let input = get(usage, "input_tokens");
+            let output = get(usage, "output_tokens");
+            if input == 0 && output == 0 {
+                return None;
+            }
+            Some(UsageRecord {
+                provider: Provider::Zed,
+                session_id: thread_id.to_string(),
+                timestamp,
+                project: project.name.clone(),
+                model: model.clone(),
+                message_id: format!("zed:{thread_id}:{key}"),
+                request_id: String::new(),
+                input_tokens: input,
+                output_tokens: output,
+                cache_creation_input_tokens: get(usage, "cache_creation_input_tokens"),
+                cache_read_input_tokens: get(usage, "cache_read_input_tokens"),
+                cache_creation_1h_input_tokens: 0,
+                reasoning_tokens: 0,
+                web_search_requests: 0,
+                web_fetch_requests: 0,
+                service_tier: None,
+                agent: None,
+                git_branch: None,
+                project_path: project.path.clone(),
+                event: None,
+                failed_turn: false,
+                tool_calls: Vec::new(),
+                account_uuid: None,
+                reported_cost: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compressed_thread_yields_one_record_per_request() {
+        let thread = serde_json::json!({
+            "model": {"provider": "zed.dev", "model": "claude-sonnet-4-5-thinking"},
+            "initial_project_snapshot": {
+                "timestamp": "2025-06-01T09:00:00Z",
+                "worktree_snapshots": [{"worktree_path": "/nonexistent/tku/zed-app"}]
+            },
+            "request_token_usage": {
+                "msg-1": {"input_tokens": 100, "output_tokens": 20,
+                          "cache_creation_input_tokens": 5, "cache_read_input_tokens": 7},
+                "msg-2": {"input_tokens": 0, "output_tokens": 0}
+            }
+        });
+        let blob = ruzstd::encoding::compress_to_vec(
+            thread.to_string().as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+
+        let json = decode_payload("zstd", &blob).unwrap();
+        let thread: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        let records = extract_records(&thread, "t1", "2025-06-02T00:00:00Z");
+
+        assert_eq!(records.len(), 1);
+        let r = &records[0];
+        assert_eq!(r.message_id, "zed:t1:msg-1");
+        assert_eq!(r.model, "claude-sonnet-4-5");
+        assert_eq!(r.project, "zed-app");
+        assert_eq!(r.timestamp.to_rfc3339(), "2025-06-01T09:00:00+00:00");
+        assert_eq!(
+            (
+                r.input_tokens,
+                r.output_tokens,
+                r.cache_creation_input_tokens,
+                r.cache_read_input_tokens
+            ),
+            (100, 20, 5, 7)
+        );
+        assert!(decode_payload("brotli", &blob).is_none());
+    }
+}
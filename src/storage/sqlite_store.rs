@@ -1,6 +1,5 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 
 use anyhow::{Context, Result};
 use rusqlite::{params, Connection};
@@ -9,7 +8,7 @@ use super::Storage;
 use crate::paths;
 use crate::types::{Provider, UsageRecord};
 
-const SCHEMA_VERSION: i64 = 3;
+const SCHEMA_VERSION: i64 = 14;
 
 pub struct SqliteStorage {
     conn: Connection,
@@ -65,7 +64,19 @@ impl SqliteStorage {
                  output_tokens                INTEGER NOT NULL,
                  cache_creation_input_tokens  INTEGER NOT NULL,
                  cache_read_input_tokens      INTEGER NOT NULL,
-                 account_uuid                 TEXT
+                 account_uuid                 TEXT,
+                 reported_cost                REAL,
+                 cache_creation_1h_input_tokens INTEGER NOT NULL,
+                 web_search_requests          INTEGER NOT NULL,
+                 web_fetch_requests           INTEGER NOT NULL,
+                 service_tier                 TEXT,
+                 agent                        TEXT,
+                 git_branch                   TEXT,
+                 project_path                 TEXT NOT NULL,
+                 reasoning_tokens             INTEGER NOT NULL,
+                 event                        TEXT,
+                 failed_turn                  INTEGER NOT NULL,
+                 tool_calls                   TEXT NOT NULL
              );
 
              CREATE INDEX IF NOT EXISTS idx_records_file_id
@@ -140,8 +151,11 @@ impl Storage for SqliteStorage {
                 file_id, session_id, timestamp, project, model,
                 message_id, request_id, input_tokens, output_tokens,
                 cache_creation_input_tokens, cache_read_input_tokens,
-                account_uuid
-             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                account_uuid, reported_cost, cache_creation_1h_input_tokens,
+                web_search_requests, web_fetch_requests, service_tier, agent, git_branch,
+                project_path, reasoning_tokens, event, failed_turn, tool_calls
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+                       ?18, ?19, ?20, ?21, ?22, ?23, ?24)",
         ) {
             Ok(mut stmt) => {
                 for r in &records {
@@ -158,6 +172,18 @@ impl Storage for SqliteStorage {
                         r.cache_creation_input_tokens as i64,
                         r.cache_read_input_tokens as i64,
                         r.account_uuid,
+                        r.reported_cost,
+                        r.cache_creation_1h_input_tokens as i64,
+                        r.web_search_requests as i64,
+                        r.web_fetch_requests as i64,
+                        r.service_tier,
+                        r.agent,
+                        r.git_branch,
+                        r.project_path,
+                        r.reasoning_tokens as i64,
+                        r.event,
+                        r.failed_turn,
+                        r.tool_calls.join(","),
                     ]) {
                         eprintln!("tku: sqlite insert record failed: {e}");
                     }
@@ -221,7 +247,10 @@ impl Storage for SqliteStorage {
             "SELECT f.provider, r.session_id, r.timestamp, r.project, r.model,
                     r.message_id, r.request_id, r.input_tokens, r.output_tokens,
                     r.cache_creation_input_tokens, r.cache_read_input_tokens,
-                    r.account_uuid
+                    r.account_uuid, r.reported_cost, r.cache_creation_1h_input_tokens,
+                    r.web_search_requests, r.web_fetch_requests, r.service_tier, r.agent,
+                    r.git_branch, r.project_path, r.reasoning_tokens, r.event,
+                    r.failed_turn, r.tool_calls
                FROM records r
                JOIN files f ON r.file_id = f.file_id",
         ) {
@@ -242,13 +271,13 @@ impl Storage for SqliteStorage {
                 )
             })?;
             let provider_str: String = row.get(0)?;
-            let provider = Provider::from_str(&provider_str).map_err(|e| {
+            let provider = Provider::from_stored(&provider_str).ok_or_else(|| {
                 rusqlite::Error::FromSqlConversionFailure(
                     0,
                     rusqlite::types::Type::Text,
                     Box::new(std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
-                        e.to_string(),
+                        format!("unknown provider: {provider_str}"),
                     )),
                 )
             })?;
@@ -265,6 +294,23 @@ impl Storage for SqliteStorage {
                 cache_creation_input_tokens: row.get::<_, i64>(9)?.max(0) as u64,
                 cache_read_input_tokens: row.get::<_, i64>(10)?.max(0) as u64,
                 account_uuid: row.get::<_, Option<String>>(11)?,
+                reported_cost: row.get::<_, Option<f64>>(12)?,
+                cache_creation_1h_input_tokens: row.get::<_, i64>(13)?.max(0) as u64,
+                reasoning_tokens: row.get::<_, i64>(20)?.max(0) as u64,
+                web_search_requests: row.get::<_, i64>(14)?.max(0) as u64,
+                web_fetch_requests: row.get::<_, i64>(15)?.max(0) as u64,
+                service_tier: row.get::<_, Option<String>>(16)?,
+                agent: row.get::<_, Option<String>>(17)?,
+                git_branch: row.get::<_, Option<String>>(18)?,
+                project_path: row.get(19)?,
+                event: row.get::<_, Option<String>>(21)?,
+                failed_turn: row.get(22)?,
+                tool_calls: row
+                    .get::<_, String>(23)?
+                    .split(',')
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect(),
             })
         })
         .map(|rows| rows.filter_map(|r| r.ok()).collect())
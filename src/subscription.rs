@@ -12,6 +12,7 @@ use crate::atomic_write::atomic_write;
 use crate::cost::PricingMap;
 use crate::exchange::ExchangeRate;
 use crate::paths;
+use crate::providers::codex::RateLimitSnapshot;
 use crate::types::{Provider, UsageRecord};
 
 const USAGE_API_URL: &str = "https://api.anthropic.com/api/oauth/usage";
@@ -472,7 +473,7 @@ pub fn run(
         return run_plan_mode(&oauth, &store, resets_at, exchange, resolved_plan, &usage);
     }
 
-    let cycles = compute_cycles(resets_at, 4);
+    let cycles = compute_cycles(resets_at, Duration::days(7), 4);
 
     // Resolve current week's utilization + source
     let (current_pct, current_source) = resolve_current_usage(
@@ -510,52 +511,8 @@ pub fn run(
         eprintln!("{} (offline — showing cached data)", tier_label);
     }
 
-    // Pace projection bar
     if let Some(pct) = current_pct {
-        if pct > 0.0 {
-            let now = Utc::now();
-            let elapsed = now.signed_duration_since(cycle_start);
-            let elapsed_h = elapsed.num_minutes() as f64 / 60.0;
-            let rate_per_h = pct / elapsed_h;
-            let cycle_remaining_h =
-                resets_at.signed_duration_since(now).num_minutes() as f64 / 60.0;
-            let projected = (pct + rate_per_h * cycle_remaining_h).min(200.0);
-
-            const BAR_W: usize = 30;
-            let used_w = ((pct / 100.0) * BAR_W as f64).round() as usize;
-            let proj_w = if projected > pct {
-                (((projected - pct) / 100.0) * BAR_W as f64).round() as usize
-            } else {
-                0
-            };
-            let used_w = used_w.min(BAR_W);
-            let proj_w = proj_w.min(BAR_W - used_w);
-            let free_w = BAR_W - used_w - proj_w;
-
-            let bar = format!(
-                "{}{}{}",
-                "█".repeat(used_w),
-                "▒".repeat(proj_w),
-                "░".repeat(free_w),
-            );
-
-            if pct >= 100.0 {
-                eprintln!("{} ▸ at capacity", bar);
-            } else if projected >= 100.0 {
-                eprintln!(
-                    "{} ▸ hits 100% in {}",
-                    bar,
-                    format_duration_short((100.0 - pct) / rate_per_h)
-                );
-            } else {
-                eprintln!(
-                    "{} ▸ ~{:.0}% at reset, {} left",
-                    bar,
-                    projected,
-                    format_duration_short(cycle_remaining_h)
-                );
-            }
-        }
+        print_pace_bar(pct, cycle_start, resets_at);
     }
 
     eprintln!();
@@ -648,6 +605,221 @@ pub fn run(
     Ok(())
 }
 
+/// Usage so far plus where the current pace lands at reset, e.g.
+/// `███▒▒░░░ ▸ ~62% at reset, 3d 4h left`.
+fn print_pace_bar(pct: f64, cycle_start: DateTime<Utc>, resets_at: DateTime<Utc>) {
+    if pct <= 0.0 {
+        return;
+    }
+    let now = Utc::now();
+    let elapsed = now.signed_duration_since(cycle_start);
+    let elapsed_h = elapsed.num_minutes() as f64 / 60.0;
+    let rate_per_h = pct / elapsed_h;
+    let cycle_remaining_h = resets_at.signed_duration_since(now).num_minutes() as f64 / 60.0;
+    let projected = (pct + rate_per_h * cycle_remaining_h).min(200.0);
+
+    const BAR_W: usize = 30;
+    let used_w = ((pct / 100.0) * BAR_W as f64).round() as usize;
+    let proj_w = if projected > pct {
+        (((projected - pct) / 100.0) * BAR_W as f64).round() as usize
+    } else {
+        0
+    };
+    let used_w = used_w.min(BAR_W);
+    let proj_w = proj_w.min(BAR_W - used_w);
+    let free_w = BAR_W - used_w - proj_w;
+
+    let bar = format!(
+        "{}{}{}",
+        "█".repeat(used_w),
+        "▒".repeat(proj_w),
+        "░".repeat(free_w),
+    );
+
+    if pct >= 100.0 {
+        eprintln!("{} ▸ at capacity", bar);
+    } else if projected >= 100.0 {
+        eprintln!(
+            "{} ▸ hits 100% in {}",
+            bar,
+            format_duration_short((100.0 - pct) / rate_per_h)
+        );
+    } else {
+        eprintln!(
+            "{} ▸ ~{:.0}% at reset, {} left",
+            bar,
+            projected,
+            format_duration_short(cycle_remaining_h)
+        );
+    }
+}
+
+// --- Codex (ChatGPT plan) ---
+
+/// Codex reports a short (5h) and a weekly window; anything shorter than a
+/// day is the short one.
+const CODEX_SHORT_WINDOW_MAX_MINUTES: u64 = 24 * 60;
+
+/// Split Codex snapshots into the series the cycle table follows and the
+/// short windows shown under its current row. The weekly window leads when
+/// Codex has logged one; otherwise the short window stands in on its own.
+fn codex_series(
+    snapshots: Vec<RateLimitSnapshot>,
+) -> (Vec<RateLimitSnapshot>, Vec<RateLimitSnapshot>) {
+    let (short, weekly): (Vec<_>, Vec<_>) = snapshots
+        .into_iter()
+        .partition(|s| s.window_minutes < CODEX_SHORT_WINDOW_MAX_MINUTES);
+    if weekly.is_empty() {
+        (short, Vec::new())
+    } else {
+        (weekly, short)
+    }
+}
+
+/// Subscription overview for Codex on a ChatGPT plan.
+///
+/// Unlike Claude there's no usage API to call: Codex logs the plan's
+/// `rate_limits` on every response, so the latest snapshot per window is
+/// read from the session files, which also works offline. Past cycles show
+/// the last utilization seen before their reset.
+pub fn run_codex(
+    exchange: &ExchangeRate,
+    records: &[UsageRecord],
+    pricing: &dyn PricingMap,
+) -> Result<()> {
+    const CYCLES: usize = 4;
+    let now = Utc::now();
+    let snapshots =
+        crate::providers::codex::rate_limit_snapshots(now - Duration::days(7 * CYCLES as i64));
+    let (series, short) = codex_series(snapshots);
+
+    let Some(latest) = series.iter().max_by_key(|s| s.observed_at) else {
+        bail!(
+            "No Codex rate-limit data found. Codex logs it to ~/.codex/sessions when signed in with a ChatGPT plan."
+        );
+    };
+
+    // Once the last seen reset has passed, the current window's usage is
+    // unknown until Codex logs another response.
+    let window = Duration::minutes(latest.window_minutes as i64);
+    let mut resets_at = latest.resets_at;
+    let mut current_pct = Some(latest.used_percent);
+    while resets_at <= now {
+        resets_at += window;
+        current_pct = None;
+    }
+    let cycle_start = resets_at - window;
+    // Named only when the short window stands in for a missing weekly one.
+    let short_only = latest.window_minutes < CODEX_SHORT_WINDOW_MAX_MINUTES;
+    let plan_label = if short_only {
+        format!(
+            "Codex (ChatGPT plan, {} window)",
+            format_duration_short(latest.window_minutes as f64 / 60.0)
+        )
+    } else {
+        "Codex (ChatGPT plan)".to_string()
+    };
+
+    let codex_records: Vec<&UsageRecord> = records
+        .iter()
+        .filter(|r| r.provider == Provider::Codex)
+        .collect();
+
+    let reset_local = resets_at.with_timezone(&chrono::Local);
+    match current_pct {
+        Some(pct) => eprintln!(
+            "{plan_label} — {:.0}% used, resets {}",
+            pct,
+            reset_local.format("%b %-d, %-I:%M%P")
+        ),
+        None => eprintln!(
+            "{plan_label} — no usage logged since the last reset, next reset {}",
+            reset_local.format("%b %-d, %-I:%M%P")
+        ),
+    }
+    if short_only {
+        eprintln!("No weekly window logged yet.");
+    }
+    if let Some(pct) = current_pct {
+        print_pace_bar(pct, cycle_start, resets_at);
+    }
+    eprintln!();
+
+    let short_latest = short
+        .iter()
+        .max_by_key(|s| s.observed_at)
+        .filter(|s| s.resets_at > now);
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+    table.set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(vec![
+        Cell::new("Period"),
+        Cell::new("Usage"),
+        Cell::new("Cost"),
+        Cell::new("$/1%"),
+    ]);
+
+    let cycles = compute_cycles(resets_at, window, CYCLES);
+    for (i, (start, end)) in cycles.iter().enumerate() {
+        let is_current = i == cycles.len() - 1;
+        let period_label = if short_only {
+            format!(
+                "{} → {}",
+                start
+                    .with_timezone(&chrono::Local)
+                    .format("%b %-d, %-I:%M%P"),
+                end.with_timezone(&chrono::Local).format("%-I:%M%P")
+            )
+        } else {
+            format!("{} → {}", start.format("%b %-d"), end.format("%b %-d"))
+        };
+
+        let usage_pct = if is_current {
+            current_pct
+        } else {
+            series
+                .iter()
+                .find(|s| (s.resets_at - *end).num_minutes().abs() < 10)
+                .map(|s| s.used_percent)
+        };
+        let usage_str = match usage_pct {
+            Some(pct) => format!("{:.0}%", pct),
+            None => "—".to_string(),
+        };
+
+        let cost = cost_in_range(&codex_records, *start, *end, pricing);
+        let cost_per_pct = match (usage_pct, cost) {
+            (Some(pct), Some(c)) if pct > 0.0 => exchange.format_cost(Some(c / pct)),
+            _ => "—".to_string(),
+        };
+
+        table.add_row(vec![
+            Cell::new(&period_label),
+            Cell::new(&usage_str),
+            Cell::new(exchange.format_cost(cost)),
+            Cell::new(&cost_per_pct),
+        ]);
+
+        if let (true, Some(s)) = (is_current, short_latest) {
+            let left_h = s.resets_at.signed_duration_since(now).num_minutes() as f64 / 60.0;
+            table.add_row(vec![
+                Cell::new(format!(
+                    "  └─ {} window",
+                    format_duration_short(s.window_minutes as f64 / 60.0)
+                )),
+                Cell::new(format!("{:.0}%", s.used_percent)),
+                Cell::new(format!("resets in {}", format_duration_short(left_h))),
+                Cell::new(""),
+            ]);
+        }
+    }
+
+    println!("{table}");
+
+    Ok(())
+}
+
 // --- Calibration logic ---
 
 /// Check if the estimated % has crossed any calibration threshold
@@ -1088,11 +1260,15 @@ fn current_cycle_from_snapshots(store: &SnapshotStore) -> Option<(DateTime<Utc>,
     Some((end - Duration::days(7), end))
 }
 
-fn compute_cycles(resets_at: DateTime<Utc>, count: usize) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+fn compute_cycles(
+    resets_at: DateTime<Utc>,
+    length: Duration,
+    count: usize,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
     let mut cycles = Vec::with_capacity(count);
     let mut end = resets_at;
     for _ in 0..count {
-        let start = end - Duration::days(7);
+        let start = end - length;
         cycles.push((start, end));
         end = start;
     }
@@ -1895,6 +2071,27 @@ fn gather_account_row(
 mod tests {
     use super::*;
 
+    #[test]
+    fn codex_short_window_stands_in_without_a_weekly_one() {
+        let snap = |window_minutes| RateLimitSnapshot {
+            observed_at: Utc::now(),
+            window_minutes,
+            used_percent: 10.0,
+            resets_at: Utc::now(),
+        };
+
+        let (series, short) = codex_series(vec![snap(300), snap(10080)]);
+        assert_eq!(series[0].window_minutes, 10080);
+        assert_eq!(short[0].window_minutes, 300);
+
+        let (series, short) = codex_series(vec![snap(300)]);
+        assert_eq!(series[0].window_minutes, 300);
+        assert!(short.is_empty());
+
+        let (series, _) = codex_series(Vec::new());
+        assert!(series.is_empty());
+    }
+
     const V1_SAMPLE: &str = r#"{
         "snapshots": [
             {
@@ -2127,7 +2324,7 @@ mod tests {
         // trigger an immediate re-upgrade after a downgrade.
         // We exercise the partition by simulating what `run_plan_mode`
         // does: feed only natives into recommend().
-        let snaps = vec![
+        let snaps = [
             // Foreign (pre-downgrade Max5x cycles, very low utilization)
             CycleSnapshot {
                 cycle_end: "2026-04-01T10:00:00Z".parse().unwrap(),
@@ -2155,7 +2352,7 @@ mod tests {
 
     #[test]
     fn recommend_mixed_plans_uses_only_natives() {
-        let snaps = vec![
+        let snaps = [
             // Foreign pre-upgrade Pro cycles at 96% — would trigger upgrade
             // if naively included, but they're foreign now.
             CycleSnapshot {
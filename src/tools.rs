@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::cost::PricingMap;
+use crate::types::{Provider, UsageRecord};
+
+#[derive(Default)]
+pub struct ToolRow {
+    pub name: String,
+    pub calls: u64,
+    /// Responses that made at least one of the calls.
+    pub responses: u64,
+    /// Those responses' cost, split across their calls. None when unpriced.
+    pub cost: Option<f64>,
+    /// For an MCP server, its tools.
+    pub tools: Vec<ToolRow>,
+}
+
+impl ToolRow {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cost: Some(0.0),
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, calls: u64, cost: Option<f64>) {
+        self.calls += calls;
+        self.responses += 1;
+        self.cost = self.cost.zip(cost).map(|(a, b)| a + b);
+    }
+
+    pub fn is_mcp_server(&self) -> bool {
+        !self.tools.is_empty()
+    }
+}
+
+pub struct ToolReport {
+    pub rows: Vec<ToolRow>,
+    pub total: ToolRow,
+}
+
+/// Split an MCP tool name into server and tool: Claude logs
+/// `mcp__<server>__<tool>`, Codex `<server>__<tool>`.
+fn mcp_tool(provider: Provider, name: &str) -> Option<(&str, &str)> {
+    match name.strip_prefix("mcp__") {
+        Some(rest) => rest.split_once("__"),
+        None if provider == Provider::Codex => name.split_once("__"),
+        None => None,
+    }
+}
+
+/// Calls per tool, with MCP tools grouped under their server. Each
+/// response's cost is split across the calls it made, so a response that
+/// ran `Bash` twice and `Read` once charges two thirds to `Bash`.
+pub fn compute(records: &[UsageRecord], pricing: &dyn PricingMap) -> ToolReport {
+    let mut tools: HashMap<String, ToolRow> = HashMap::new();
+    let mut servers: HashMap<String, (ToolRow, HashMap<String, ToolRow>)> = HashMap::new();
+    let mut total = ToolRow::new("TOTAL");
+
+    for r in records.iter().filter(|r| !r.tool_calls.is_empty()) {
+        let cost = pricing.cost_for_record(r);
+        let per_call = cost.map(|c| c / r.tool_calls.len() as f64);
+        total.add(r.tool_calls.len() as u64, cost);
+
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        for name in &r.tool_calls {
+            *counts.entry(name.as_str()).or_default() += 1;
+        }
+
+        let mut server_calls: HashMap<&str, u64> = HashMap::new();
+        for (name, n) in counts {
+            let share = per_call.map(|c| c * n as f64);
+            match mcp_tool(r.provider, name) {
+                Some((server, tool)) => {
+                    *server_calls.entry(server).or_default() += n;
+                    servers
+                        .entry(server.to_string())
+                        .or_insert_with(|| (ToolRow::new(server), HashMap::new()))
+                        .1
+                        .entry(tool.to_string())
+                        .or_insert_with(|| ToolRow::new(tool))
+                        .add(n, share);
+                }
+                None => tools
+                    .entry(name.to_string())
+                    .or_insert_with(|| ToolRow::new(name))
+                    .add(n, share),
+            }
+        }
+        for (server, n) in server_calls {
+            if let Some((row, _)) = servers.get_mut(server) {
+                row.add(n, per_call.map(|c| c * n as f64));
+            }
+        }
+    }
+
+    let mut rows: Vec<ToolRow> = tools.into_values().collect();
+    for (mut row, server_tools) in servers.into_values() {
+        row.tools = server_tools.into_values().collect();
+        sort_rows(&mut row.tools);
+        rows.push(row);
+    }
+    sort_rows(&mut rows);
+
+    ToolReport { rows, total }
+}
+
+fn sort_rows(rows: &mut [ToolRow]) {
+    rows.sort_by(|a, b| {
+        match (a.cost, b.cost) {
+            (Some(x), Some(y)) => y.partial_cmp(&x).unwrap_or(std::cmp::Ordering::Equal),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+        .then(b.calls.cmp(&a.calls))
+        .then(a.name.cmp(&b.name))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::ModelPricing;
+    use chrono::{DateTime, Utc};
+
+    struct TestPricing(ModelPricing);
+    impl PricingMap for TestPricing {
+        fn get(&self, _model: &str) -> Option<&ModelPricing> {
+            Some(&self.0)
+        }
+    }
+
+    fn pricing() -> TestPricing {
+        TestPricing(ModelPricing {
+            input_cost_per_token: 1.0,
+            output_cost_per_token: 0.0,
+            cache_read_input_token_cost: None,
+            cache_creation_input_token_cost: None,
+            cache_creation_input_token_cost_above_1hr: None,
+            output_cost_per_reasoning_token: None,
+            web_search_cost_per_request: None,
+            web_fetch_cost_per_request: None,
+            tiers: Vec::new(),
+        })
+    }
+
+    fn rec(provider: Provider, input: u64, tools: &[&str]) -> UsageRecord {
+        let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        UsageRecord {
+            provider,
+            session_id: "s".into(),
+            timestamp,
+            project: "proj".into(),
+            model: "m".into(),
+            message_id: "m".into(),
+            request_id: "r".into(),
+            input_tokens: input,
+            output_tokens: 0,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+            cache_creation_1h_input_tokens: 0,
+            reasoning_tokens: 0,
+            web_search_requests: 0,
+            web_fetch_requests: 0,
+            service_tier: None,
+            agent: None,
+            git_branch: None,
+            project_path: String::new(),
+            event: None,
+            failed_turn: false,
+            tool_calls: tools.iter().map(|t| t.to_string()).collect(),
+            account_uuid: None,
+            reported_cost: None,
+        }
+    }
+
+    fn row<'a>(rows: &'a [ToolRow], name: &str) -> &'a ToolRow {
+        rows.iter().find(|r| r.name == name).unwrap()
+    }
+
+    #[test]
+    fn cost_splits_per_call_and_mcp_tools_group_by_server() {
+        let recs = vec![
+            rec(
+                Provider::Claude,
+                30,
+                &["Bash", "Bash", "mcp__github__create_issue"],
+            ),
+            rec(Provider::Codex, 10, &["github__list_prs"]),
+            rec(Provider::Claude, 99, &[]),
+        ];
+        let report = compute(&recs, &pricing());
+
+        let bash = row(&report.rows, "Bash");
+        assert_eq!((bash.calls, bash.responses), (2, 1));
+        assert_eq!(bash.cost, Some(20.0));
+
+        let github = row(&report.rows, "github");
+        assert!(github.is_mcp_server());
+        assert_eq!((github.calls, github.responses), (2, 2));
+        assert_eq!(github.cost, Some(20.0));
+        assert_eq!(row(&github.tools, "create_issue").cost, Some(10.0));
+        assert_eq!(row(&github.tools, "list_prs").cost, Some(10.0));
+
+        assert_eq!((report.total.calls, report.total.responses), (4, 2));
+        assert_eq!(report.total.cost, Some(40.0));
+    }
+}
@@ -1,14 +1,19 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Which LLM tool a [`UsageRecord`] came from.
 ///
-/// Stored on disk as a lowercase string (via `serde(rename_all = "lowercase")`)
-/// so JSON output and the sqlite `provider` TEXT column stay byte-identical
-/// to the previous `provider: String` representation. The bitcode cache
-/// re-serializes through the same serde path.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+/// Stored on disk as a lowercase string so JSON output and the sqlite
+/// `provider` TEXT column stay byte-identical to the previous
+/// `provider: String` representation. The bitcode cache re-serializes
+/// through the same serde path.
+///
+/// `Custom` carries the user-chosen name of a `[[providers.custom]]` tool.
+/// Names are interned (see [`Provider::custom`]) so the type stays `Copy`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Provider {
     Claude,
     Codex,
@@ -19,6 +24,18 @@ pub enum Provider {
     OpenClaw,
     Droid,
     Kimi,
+    Cline,
+    RooCode,
+    KiloCode,
+    Aider,
+    Goose,
+    Qwen,
+    Llm,
+    Zed,
+    Crush,
+    Continue,
+    Import,
+    Custom(&'static str),
 }
 
 impl Provider {
@@ -33,9 +50,84 @@ impl Provider {
             Self::OpenClaw => "openclaw",
             Self::Droid => "droid",
             Self::Kimi => "kimi",
+            Self::Cline => "cline",
+            Self::RooCode => "roocode",
+            Self::KiloCode => "kilocode",
+            Self::Aider => "aider",
+            Self::Goose => "goose",
+            Self::Qwen => "qwen",
+            Self::Llm => "llm",
+            Self::Zed => "zed",
+            Self::Crush => "crush",
+            Self::Continue => "continue",
+            Self::Import => "import",
+            Self::Custom(name) => name,
         }
     }
 
+    /// Built-in provider by name, including aliases.
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "claude" => Some(Self::Claude),
+            "codex" => Some(Self::Codex),
+            "gemini" => Some(Self::Gemini),
+            "pi" => Some(Self::Pi),
+            "amp" => Some(Self::Amp),
+            "opencode" => Some(Self::OpenCode),
+            "openclaw" => Some(Self::OpenClaw),
+            "droid" => Some(Self::Droid),
+            "kimi" => Some(Self::Kimi),
+            "cline" => Some(Self::Cline),
+            "roocode" | "roo-code" => Some(Self::RooCode),
+            "kilocode" | "kilo-code" => Some(Self::KiloCode),
+            "aider" => Some(Self::Aider),
+            "goose" => Some(Self::Goose),
+            "qwen" | "qwen-code" => Some(Self::Qwen),
+            "llm" => Some(Self::Llm),
+            "zed" => Some(Self::Zed),
+            "crush" => Some(Self::Crush),
+            "continue" | "continue-dev" => Some(Self::Continue),
+            "import" => Some(Self::Import),
+            _ => None,
+        }
+    }
+
+    /// A config-defined tool, registered under `name` so `--tool` accepts it.
+    /// Returns None for names that aren't lowercase `[a-z0-9_-]` (the name
+    /// ends up in cache file paths) or that would shadow a built-in provider.
+    pub fn custom(name: &str) -> Option<Self> {
+        let valid = !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+        if !valid || Self::builtin(name).is_some() {
+            return None;
+        }
+
+        let mut names = custom_names();
+        let interned = match names.get(name) {
+            Some(n) => *n,
+            None => {
+                let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+                names.insert(leaked);
+                leaked
+            }
+        };
+        Some(Self::Custom(interned))
+    }
+
+    /// A custom tool registered by [`Provider::custom`], without registering
+    /// anything new.
+    fn registered(name: &str) -> Option<Self> {
+        custom_names().get(name).map(|n| Self::Custom(n))
+    }
+
+    /// Provider name read back from the cache. Records of a custom tool that
+    /// has since left the config still load, so its name is registered.
+    pub fn from_stored(s: &str) -> Option<Self> {
+        Self::builtin(s).or_else(|| Self::custom(s))
+    }
+
     #[allow(dead_code)]
     pub fn iter() -> impl Iterator<Item = Self> {
         [
@@ -48,33 +140,60 @@ impl Provider {
             Self::OpenClaw,
             Self::Droid,
             Self::Kimi,
+            Self::Cline,
+            Self::RooCode,
+            Self::KiloCode,
+            Self::Aider,
+            Self::Goose,
+            Self::Qwen,
+            Self::Llm,
+            Self::Zed,
+            Self::Crush,
+            Self::Continue,
+            Self::Import,
         ]
         .into_iter()
     }
 }
 
+/// Interned names of the custom tools; see [`Provider::custom`].
+fn custom_names() -> std::sync::MutexGuard<'static, HashSet<&'static str>> {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
 impl std::fmt::Display for Provider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.as_str())
     }
 }
 
+impl Serialize for Provider {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Provider {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_stored(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown provider: {s}")))
+    }
+}
+
+/// Parses a built-in name or a custom tool registered from the config.
 impl std::str::FromStr for Provider {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "claude" => Ok(Self::Claude),
-            "codex" => Ok(Self::Codex),
-            "gemini" => Ok(Self::Gemini),
-            "pi" => Ok(Self::Pi),
-            "amp" => Ok(Self::Amp),
-            "opencode" => Ok(Self::OpenCode),
-            "openclaw" => Ok(Self::OpenClaw),
-            "droid" => Ok(Self::Droid),
-            "kimi" => Ok(Self::Kimi),
-            _ => anyhow::bail!("unknown provider: {s}"),
-        }
+        let name = s.to_lowercase();
+        Self::builtin(&name)
+            .or_else(|| Self::registered(&name))
+            .ok_or_else(|| anyhow::anyhow!("unknown provider: {s}"))
     }
 }
 
@@ -91,6 +210,54 @@ pub struct UsageRecord {
     pub output_tokens: u64,
     pub cache_creation_input_tokens: u64,
     pub cache_read_input_tokens: u64,
+    /// The part of `cache_creation_input_tokens` written with Anthropic's
+    /// 1-hour cache TTL, which is billed at a higher rate than the default
+    /// 5-minute writes. Zero for providers that don't split cache writes.
+    #[serde(default)]
+    pub cache_creation_1h_input_tokens: u64,
+    /// Reasoning/thinking tokens, billed as output but kept apart from
+    /// `output_tokens` (which counts only the visible response).
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+    /// Server-side tool calls (Claude's `usage.server_tool_use`), billed per
+    /// request on top of tokens.
+    #[serde(default)]
+    pub web_search_requests: u64,
+    #[serde(default)]
+    pub web_fetch_requests: u64,
+    /// Capacity tier the request ran on (`standard`, `priority`, `flex`,
+    /// `batch`), normalized by `normalize_service_tier`. None when the tool
+    /// doesn't log it, which is priced as standard.
+    #[serde(default)]
+    pub service_tier: Option<String>,
+    /// Subagent that produced this record (e.g. `Explore`, or `subagent`
+    /// when the type isn't known). None for the main conversation thread.
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Git branch checked out in the working directory at the time.
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    /// Repository root (or working directory) `project` was resolved from;
+    /// unique where display names may collide. Empty when the tool doesn't
+    /// log a working directory.
+    #[serde(default)]
+    pub project_path: String,
+    /// Set on records that mark something happening in a session rather
+    /// than usage (Claude only): a failed call (`api_error`, `overloaded`,
+    /// `rate_limited`), an `interrupted` turn, or a context compaction
+    /// (`auto_compact`, `manual_compact`). Failures carry no tokens;
+    /// compactions carry the estimated summarization call, which the
+    /// transcript doesn't log. Left out of usage reports.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// Usage from a turn that ended in an API error or an interruption, so
+    /// the work was retried or thrown away.
+    #[serde(default)]
+    pub failed_turn: bool,
+    /// Names of the tools this response invoked, one entry per call (e.g.
+    /// `Bash`, `mcp__github__create_issue`). Claude and Codex only.
+    #[serde(default)]
+    pub tool_calls: Vec<String>,
     /// Organization UUID of the Claude account that produced this record,
     /// captured at scan time from `~/.claude/.credentials.json`. None for
     /// non-Claude providers, for records cached before this field existed,
@@ -99,6 +266,46 @@ pub struct UsageRecord {
     /// switch log when this is None.
     #[serde(default)]
     pub account_uuid: Option<String>,
+    /// Cost in USD as recorded by the tool itself, for providers that log
+    /// what the upstream charged. None when the tool doesn't record one.
+    #[serde(default)]
+    pub reported_cost: Option<f64>,
+}
+
+/// Map a logged service tier onto the names pricing knows about. APIs
+/// report the default tier under several names, and Codex calls priority
+/// processing "fast".
+pub fn normalize_service_tier(raw: &str) -> String {
+    let tier = raw.trim().to_ascii_lowercase();
+    match tier.as_str() {
+        "" | "default" | "auto" | "standard" | "standard_only" | "on_demand" => {
+            "standard".to_string()
+        }
+        "fast" => "priority".to_string(),
+        _ => tier,
+    }
+}
+
+impl UsageRecord {
+    /// Display label for the thread that produced the record.
+    pub fn agent_label(&self) -> &str {
+        self.agent.as_deref().unwrap_or("main")
+    }
+
+    /// A failed API call or an interrupted turn.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self.event.as_deref(),
+            Some("api_error" | "overloaded" | "rate_limited" | "interrupted")
+        )
+    }
+
+    pub fn is_compaction(&self) -> bool {
+        matches!(
+            self.event.as_deref(),
+            Some("auto_compact" | "manual_compact")
+        )
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -107,10 +314,20 @@ pub struct AggregatedBucket {
     pub output_tokens: u64,
     pub cache_creation_input_tokens: u64,
     pub cache_read_input_tokens: u64,
+    /// 1-hour TTL share of `cache_creation_input_tokens`.
+    pub cache_creation_1h_input_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub web_search_requests: u64,
+    pub web_fetch_requests: u64,
     pub cost: Option<f64>,
+    /// Reported minus computed cost, over records that have both.
+    pub cost_discrepancy: Option<f64>,
     pub models: Vec<String>,
     pub projects: Vec<String>,
     pub tools: Vec<String>,
+    pub tiers: Vec<String>,
+    pub agents: Vec<String>,
+    pub branches: Vec<String>,
     pub details: Vec<ModelBucketDetail>,
 }
 
@@ -150,6 +367,15 @@ impl AggregatedBucket {
             other.cache_read_input_tokens,
             other.cost,
         );
+        self.cache_creation_1h_input_tokens += other.cache_creation_1h_input_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.web_search_requests += other.web_search_requests;
+        self.web_fetch_requests += other.web_fetch_requests;
+        merge_cost(&mut self.cost_discrepancy, other.cost_discrepancy);
+    }
+
+    pub fn add_discrepancy(&mut self, discrepancy: Option<f64>) {
+        merge_cost(&mut self.cost_discrepancy, discrepancy);
     }
 }
 
@@ -160,7 +386,12 @@ pub struct ModelBucketDetail {
     pub output_tokens: u64,
     pub cache_creation_input_tokens: u64,
     pub cache_read_input_tokens: u64,
+    pub cache_creation_1h_input_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub web_search_requests: u64,
+    pub web_fetch_requests: u64,
     pub cost: Option<f64>,
+    pub cost_discrepancy: Option<f64>,
 }
 
 impl ModelBucketDetail {
@@ -179,4 +410,8 @@ impl ModelBucketDetail {
         self.cache_read_input_tokens += cache_read_input_tokens;
         merge_cost(&mut self.cost, cost);
     }
+
+    pub fn add_discrepancy(&mut self, discrepancy: Option<f64>) {
+        merge_cost(&mut self.cost_discrepancy, discrepancy);
+    }
 }
@@ -17,6 +17,7 @@ pub fn run(
     watch_interval: u64,
     cli: &cli::Cli,
     pricing_source: &crate::pricing::PricingSource,
+    cost_basis: crate::cost::CostBasis,
     currency: &str,
     date_range: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
 ) -> Result<()> {
@@ -28,7 +29,7 @@ pub fn run(
     };
 
     // Load pricing and exchange rate once upfront
-    let pricing = crate::pricing::load_pricing(pricing_source, cli.offline)?;
+    let pricing = crate::pricing::load_pricing(pricing_source, cli.offline)?.with_basis(cost_basis);
     let exchange = crate::exchange::load_exchange_rate(currency, cli.offline);
 
     // Initial render
@@ -81,7 +82,7 @@ pub fn run(
 fn scan_and_filter(
     cli: &cli::Cli,
     date_range: Option<(chrono::NaiveDate, chrono::NaiveDate)>,
-) -> Vec<crate::types::UsageRecord> {
+) -> Result<Vec<crate::types::UsageRecord>> {
     let mut store = crate::storage::default_storage();
 
     for provider in crate::providers::all_providers() {
@@ -90,17 +91,15 @@ fn scan_and_filter(
 
     store.flush();
     let all_records = store.drain_all();
-    let records = crate::dedup::dedup(all_records);
+    let mut records = crate::dedup::dedup(all_records);
+    crate::project::label(&mut records, crate::config::load_config().projects.name);
+    records.retain(|r| r.event.is_none());
 
     let proj_needle = cli.project.as_ref().map(|p| p.to_lowercase());
-    // Matches main.rs: three-state filter so an unknown --tool name drops all
-    // records rather than matching everything.
-    let tool_needle: Option<Result<Provider, ()>> = cli
-        .tool
-        .as_ref()
-        .map(|t| Provider::from_str(t).map_err(|_| ()));
-
-    records
+    // Matches main.rs: an unknown --tool name is an error, not an empty view.
+    let tool_needle: Option<Provider> = cli.tool.as_deref().map(Provider::from_str).transpose()?;
+
+    Ok(records
         .into_iter()
         .filter(|r| match date_range {
             Some((from, to)) => {
@@ -109,16 +108,14 @@ fn scan_and_filter(
             }
             None => true,
         })
-        .filter(|r| match &proj_needle {
-            Some(needle) => r.project.to_lowercase().contains(needle),
-            None => true,
-        })
-        .filter(|r| match tool_needle {
-            None => true,
-            Some(Ok(needle)) => r.provider == needle,
-            Some(Err(())) => false,
+        .filter(|r| {
+            proj_needle
+                .as_deref()
+                .is_none_or(|needle| crate::project::matches(r, needle))
         })
-        .collect()
+        .filter(|r| tool_needle.is_none_or(|needle| r.provider == needle))
+        .filter(|r| crate::matches_branch(r, cli.branch.as_deref()))
+        .collect())
 }
 
 fn render(
@@ -129,7 +126,7 @@ fn render(
     full: bool,
     label: &str,
 ) -> Result<()> {
-    let records = scan_and_filter(cli, date_range);
+    let records = scan_and_filter(cli, date_range)?;
 
     if full {
         render_full(&records, cli, pricing, exchange)?;